@@ -0,0 +1,75 @@
+use crate::AssemblerError;
+
+/// Size of the trailer appended to checksummed payloads.
+pub const TRAILER_SIZE: usize = 4;
+
+/// Lookup table of the reflected CRC-32 (IEEE 802.3) polynomial.
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+};
+
+/// Computes the CRC-32 (IEEE 802.3) checksum of `bytes`.
+#[must_use]
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(u32::MAX, |crc, byte| {
+        CRC32_TABLE[((crc ^ u32::from(*byte)) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// Appends the big-endian CRC-32 of `payload` as a trailer.
+#[must_use]
+pub fn append(payload: &[u8]) -> Vec<u8> {
+    let mut byte_vector = Vec::with_capacity(payload.len() + TRAILER_SIZE);
+    byte_vector.extend_from_slice(payload);
+    byte_vector.extend_from_slice(&crc32(payload).to_be_bytes());
+    byte_vector
+}
+
+/// Verifies and strips the CRC-32 trailer of `byte_vector`.
+///
+/// # Parameters
+/// - `byte_vector`: Payload followed by its checksum trailer.
+/// - `session_id`: Session the payload belongs to, reported in errors.
+///
+/// # Errors
+///
+/// Returns `AssemblerError::MissingChecksum` if `byte_vector` is shorter than the
+/// trailer and `AssemblerError::ChecksumMismatch` if the payload was corrupted.
+pub fn verify(
+    mut byte_vector: Vec<u8>,
+    session_id: Option<u64>,
+) -> Result<Vec<u8>, AssemblerError> {
+    let Some(payload_length) = byte_vector.len().checked_sub(TRAILER_SIZE) else {
+        return Err(AssemblerError::MissingChecksum { session_id });
+    };
+    let mut trailer = [0; TRAILER_SIZE];
+    trailer.copy_from_slice(&byte_vector[payload_length..]);
+    byte_vector.truncate(payload_length);
+
+    let expected = u32::from_be_bytes(trailer);
+    let actual = crc32(&byte_vector);
+    if expected != actual {
+        return Err(AssemblerError::ChecksumMismatch {
+            session_id,
+            expected,
+            actual,
+        });
+    }
+    Ok(byte_vector)
+}
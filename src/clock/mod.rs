@@ -0,0 +1,55 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Source of the current time for timeouts, so that they can be tested
+/// deterministically with a `ManualClock`.
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+/// Clock reading the system time with `Instant::now`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Clock that only moves when told to. Clones share the same time, so a test
+/// can keep a clone and advance the clock owned by the code under test.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl ManualClock {
+    #[must_use]
+    pub fn new() -> Self {
+        ManualClock {
+            now: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Moves the clock forward by `duration`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if another thread panicked while accessing the clock.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+}
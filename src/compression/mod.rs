@@ -0,0 +1,248 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use crate::pipeline::PayloadTransform;
+use crate::AssemblerError;
+
+/// Flag byte of a payload sent as is, because compressing it would not shrink it.
+pub const FLAG_STORED: u8 = 0;
+/// Flag byte of a compressed payload, followed by its big-endian `u64` original
+/// length and an LZ4-style block.
+pub const FLAG_COMPRESSED: u8 = 1;
+
+/// Size of the header of a compressed payload: flag and original length.
+const COMPRESSED_HEADER_SIZE: usize = 1 + 8;
+/// Shortest match worth encoding, as a match costs at least three bytes.
+const MIN_MATCH: usize = 4;
+/// Farthest back a match can start, as offsets are encoded as `u16`.
+const MAX_OFFSET: usize = u16::MAX as usize;
+/// Number of bits of the hash of four bytes indexing the match table.
+const HASH_LOG: u32 = 12;
+
+/// Reasons for rejecting a compressed payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompressionError {
+    /// The payload does not even carry a flag byte.
+    Empty,
+    UnknownFlag(u8),
+    /// The compressed block ends in the middle of a sequence.
+    Truncated,
+    /// A match refers to bytes before the start of the payload.
+    InvalidOffset(usize),
+    /// The decompressed bytes do not match the original length in the header.
+    LengthMismatch {
+        declared: u64,
+        actual: u64,
+    },
+}
+
+impl Display for CompressionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompressionError::Empty => write!(f, "payload carries no compression flag"),
+            CompressionError::UnknownFlag(flag) => write!(f, "unknown compression flag {flag}"),
+            CompressionError::Truncated => write!(f, "compressed block is truncated"),
+            CompressionError::InvalidOffset(offset) => {
+                write!(f, "match offset {offset} is out of range")
+            }
+            CompressionError::LengthMismatch { declared, actual } => write!(
+                f,
+                "compressed payload declares {declared} bytes but expands to {actual}"
+            ),
+        }
+    }
+}
+
+impl Error for CompressionError {}
+
+/// Compresses `payload`, falling back to storing it when compression would not
+/// shrink it. Either way the result starts with a flag byte telling `decompress`
+/// what to do.
+#[must_use]
+pub fn compress(payload: &[u8]) -> Vec<u8> {
+    let mut compressed = Vec::with_capacity(payload.len() / 2 + COMPRESSED_HEADER_SIZE);
+    compressed.push(FLAG_COMPRESSED);
+    compressed.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+    compress_block(payload, &mut compressed);
+    if compressed.len() < payload.len() + 1 {
+        return compressed;
+    }
+
+    let mut stored = Vec::with_capacity(payload.len() + 1);
+    stored.push(FLAG_STORED);
+    stored.extend_from_slice(payload);
+    stored
+}
+
+/// Restores a payload produced by `compress`.
+///
+/// # Errors
+///
+/// Returns a `CompressionError` if `byte_vector` was not produced by `compress`
+/// or was corrupted.
+pub fn decompress(byte_vector: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    let (flag, block) = byte_vector.split_first().ok_or(CompressionError::Empty)?;
+    match *flag {
+        FLAG_STORED => Ok(block.to_vec()),
+        FLAG_COMPRESSED => {
+            let (length, block) = block
+                .split_first_chunk::<8>()
+                .ok_or(CompressionError::Truncated)?;
+            decompress_block(block, u64::from_be_bytes(*length))
+        }
+        flag => Err(CompressionError::UnknownFlag(flag)),
+    }
+}
+
+/// Appends the LZ4-style block of `input` to `output`: a sequence of literal
+/// runs each followed by a match, the last run having no match.
+fn compress_block(input: &[u8], output: &mut Vec<u8>) {
+    // Last position at which each hash of four bytes was seen
+    let mut table = vec![usize::MAX; 1 << HASH_LOG];
+    let mut anchor = 0;
+    let mut position = 0;
+    while position + MIN_MATCH <= input.len() {
+        let sequence = &input[position..position + MIN_MATCH];
+        let hash = hash(sequence);
+        let candidate = table[hash];
+        table[hash] = position;
+
+        if candidate == usize::MAX
+            || position - candidate > MAX_OFFSET
+            || &input[candidate..candidate + MIN_MATCH] != sequence
+        {
+            position += 1;
+            continue;
+        }
+        let mut length = MIN_MATCH;
+        while position + length < input.len()
+            && input[candidate + length] == input[position + length]
+        {
+            length += 1;
+        }
+        write_sequence(
+            output,
+            &input[anchor..position],
+            Some((position - candidate, length)),
+        );
+        position += length;
+        anchor = position;
+    }
+    write_sequence(output, &input[anchor..], None);
+}
+
+fn hash(sequence: &[u8]) -> usize {
+    let sequence = u32::from_le_bytes([sequence[0], sequence[1], sequence[2], sequence[3]]);
+    (sequence.wrapping_mul(2_654_435_761) >> (32 - HASH_LOG)) as usize
+}
+
+/// Appends a token, the literals and, unless this is the last sequence, the
+/// offset and length of the following match.
+fn write_sequence(output: &mut Vec<u8>, literals: &[u8], matched: Option<(usize, usize)>) {
+    let match_length = matched.map_or(0, |(_, length)| length - MIN_MATCH);
+    output.push(((literals.len().min(15) << 4) | match_length.min(15)) as u8);
+    if literals.len() >= 15 {
+        write_length(output, literals.len() - 15);
+    }
+    output.extend_from_slice(literals);
+    if let Some((offset, _)) = matched {
+        // Fits into u16, as it is not larger than MAX_OFFSET
+        output.extend_from_slice(&(offset as u16).to_le_bytes());
+        if match_length >= 15 {
+            write_length(output, match_length - 15);
+        }
+    }
+}
+
+fn write_length(output: &mut Vec<u8>, mut length: usize) {
+    while length >= 255 {
+        output.push(255);
+        length -= 255;
+    }
+    output.push(length as u8);
+}
+
+fn decompress_block(block: &[u8], declared: u64) -> Result<Vec<u8>, CompressionError> {
+    let length_mismatch = |actual: usize| CompressionError::LengthMismatch {
+        declared,
+        actual: actual as u64,
+    };
+    // A block expands at most 255 times, so a larger length is a lie and must not
+    // be preallocated
+    let capacity = declared.min(block.len() as u64 * 255) as usize;
+    let mut output: Vec<u8> = Vec::with_capacity(capacity);
+    let mut position = 0;
+    loop {
+        let token = *block.get(position).ok_or(CompressionError::Truncated)?;
+        position += 1;
+
+        let literal_length = read_length(block, &mut position, token >> 4)?;
+        let literals = position
+            .checked_add(literal_length)
+            .and_then(|end| block.get(position..end))
+            .ok_or(CompressionError::Truncated)?;
+        if (output.len() + literals.len()) as u64 > declared {
+            return Err(length_mismatch(output.len() + literals.len()));
+        }
+        output.extend_from_slice(literals);
+        position += literal_length;
+        if position == block.len() {
+            break;
+        }
+
+        let offset = block
+            .get(position..position + 2)
+            .map(|offset| usize::from(u16::from_le_bytes([offset[0], offset[1]])))
+            .ok_or(CompressionError::Truncated)?;
+        position += 2;
+        if offset == 0 || offset > output.len() {
+            return Err(CompressionError::InvalidOffset(offset));
+        }
+        let match_length =
+            read_length(block, &mut position, token & 0x0F)?.saturating_add(MIN_MATCH);
+        if output.len().saturating_add(match_length) as u64 > declared {
+            return Err(length_mismatch(output.len().saturating_add(match_length)));
+        }
+        // Byte by byte, as a match may overlap the bytes it produces
+        let start = output.len() - offset;
+        for index in start..start + match_length {
+            output.push(output[index]);
+        }
+    }
+
+    if output.len() as u64 != declared {
+        return Err(length_mismatch(output.len()));
+    }
+    Ok(output)
+}
+
+/// Reads a length starting from the nibble of a token, continued by bytes of 255
+/// when the nibble is 15.
+fn read_length(block: &[u8], position: &mut usize, nibble: u8) -> Result<usize, CompressionError> {
+    let mut length = usize::from(nibble);
+    if nibble < 15 {
+        return Ok(length);
+    }
+    loop {
+        let byte = *block.get(*position).ok_or(CompressionError::Truncated)?;
+        *position += 1;
+        length = length.saturating_add(usize::from(byte));
+        if byte < 255 {
+            return Ok(length);
+        }
+    }
+}
+
+/// Compresses payloads with `compress`, see `PayloadTransform`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Compression;
+
+impl PayloadTransform for Compression {
+    fn encode(&mut self, payload: &[u8]) -> Result<Vec<u8>, AssemblerError> {
+        Ok(compress(payload))
+    }
+
+    fn decode(&mut self, payload: &[u8]) -> Result<Vec<u8>, AssemblerError> {
+        Ok(decompress(payload)?)
+    }
+}
@@ -0,0 +1,181 @@
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use wg_2024::network::NodeId;
+use wg_2024::packet::FRAGMENT_DSIZE;
+
+use crate::AssemblerError;
+
+/// Default maximum size of a single message, 16 MiB.
+const DEFAULT_MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+/// Settings of the reassembly path, protecting receivers from fragments that
+/// announce messages too large to be buffered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssemblerConfig {
+    /// Maximum size in bytes of a single message. A message is accepted only if
+    /// `total_n_fragments` full fragments fit into this size.
+    pub max_message_size: usize,
+    /// Maximum `total_n_fragments` of a single message.
+    pub max_fragments_per_message: u64,
+    /// Maximum number of incomplete messages held for a single source.
+    pub max_sessions_per_source: usize,
+    /// Maximum number of completed messages remembered for a single source, to
+    /// recognize fragments retransmitted after they were returned. The oldest
+    /// one is forgotten first.
+    pub max_completed_per_source: usize,
+    /// Maximum number of bytes held across all incomplete messages.
+    pub max_buffered_bytes: usize,
+    /// Time after which an incomplete message that received no fragment is evicted.
+    pub idle_timeout: Duration,
+    /// How fragments received more than once are treated.
+    pub duplicate_policy: DuplicatePolicy,
+    /// Whether fragments are rejected on any protocol violation, see
+    /// `validation::validate_fragments`.
+    pub strict: bool,
+    /// Whether completed messages carry a checksum trailer to be verified and stripped.
+    pub verify_checksum: bool,
+}
+
+impl Default for AssemblerConfig {
+    fn default() -> Self {
+        AssemblerConfig {
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            max_fragments_per_message: DEFAULT_MAX_MESSAGE_SIZE.div_ceil(FRAGMENT_DSIZE) as u64,
+            max_sessions_per_source: 64,
+            max_completed_per_source: 64,
+            max_buffered_bytes: 4 * DEFAULT_MAX_MESSAGE_SIZE,
+            idle_timeout: Duration::from_secs(30),
+            duplicate_policy: DuplicatePolicy::Reject,
+            strict: false,
+            verify_checksum: false,
+        }
+    }
+}
+
+/// Settings of the retransmission timers of a `SendBuffer`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SendConfig {
+    /// Retransmission timeout used until a round-trip time has been measured.
+    pub initial_rto: Duration,
+    /// Lower bound of the retransmission timeout.
+    pub min_rto: Duration,
+    /// Upper bound of the retransmission timeout, including backoff.
+    pub max_rto: Duration,
+    /// Number of times a fragment is retransmitted on timeout before giving up.
+    pub max_retries: u32,
+}
+
+impl Default for SendConfig {
+    fn default() -> Self {
+        SendConfig {
+            initial_rto: Duration::from_secs(1),
+            min_rto: Duration::from_millis(200),
+            max_rto: Duration::from_secs(60),
+            max_retries: 5,
+        }
+    }
+}
+
+/// Settings of the congestion window of a `WindowedSender`, in fragments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowConfig {
+    /// Number of fragments a new session may have in flight.
+    pub initial_window: usize,
+    /// Lower bound of the window when it shrinks after drops.
+    pub min_window: usize,
+    /// Upper bound of the window when it grows after `Ack`s.
+    pub max_window: usize,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            initial_window: 4,
+            min_window: 1,
+            max_window: 64,
+        }
+    }
+}
+
+/// How reassembly treats a fragment whose `fragment_index` was already received,
+/// e.g. because the sender retransmitted it after losing an `Ack`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    /// Fail with `AssemblerError::DuplicateIndex`.
+    #[default]
+    Reject,
+    /// Keep the first fragment and silently drop the duplicate.
+    Ignore,
+    /// Drop the duplicate if it carries the same `length` and data as the first
+    /// fragment, fail with `AssemblerError::ConflictingDuplicate` otherwise.
+    VerifyIdentical,
+}
+
+/// Limit of an `AssemblerConfig` that a fragment would exceed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Limit {
+    MessageSize { limit: usize, requested: u64 },
+    FragmentsPerMessage { limit: u64, requested: u64 },
+    SessionsPerSource { source: NodeId, limit: usize },
+    BufferedBytes { limit: usize, requested: u64 },
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Limit::MessageSize { limit, requested } => write!(
+                f,
+                "message of up to {requested} bytes exceeds the maximum message size of {limit} bytes"
+            ),
+            Limit::FragmentsPerMessage { limit, requested } => write!(
+                f,
+                "message of {requested} fragments exceeds the maximum of {limit} fragments"
+            ),
+            Limit::SessionsPerSource { source, limit } => write!(
+                f,
+                "node {source} already has the maximum of {limit} incomplete messages"
+            ),
+            Limit::BufferedBytes { limit, requested } => write!(
+                f,
+                "buffering {requested} bytes exceeds the maximum of {limit} buffered bytes"
+            ),
+        }
+    }
+}
+
+/// Checks that a message of `total_n_fragments` fragments is within the
+/// `max_fragments_per_message` and `max_message_size` of `config`. A message is
+/// accepted only if that many full fragments fit into the maximum size.
+///
+/// # Returns
+/// Returns the number of bytes of `total_n_fragments` full fragments.
+pub(crate) fn check_message_limits(
+    config: &AssemblerConfig,
+    total_n_fragments: u64,
+) -> Result<u64, AssemblerError> {
+    check_fragments_per_message(config, total_n_fragments)?;
+    let requested = total_n_fragments.saturating_mul(FRAGMENT_DSIZE as u64);
+    if requested > config.max_message_size as u64 {
+        return Err(AssemblerError::LimitExceeded(Limit::MessageSize {
+            limit: config.max_message_size,
+            requested,
+        }));
+    }
+    Ok(requested)
+}
+
+/// Checks that a message of `total_n_fragments` fragments is within the
+/// `max_fragments_per_message` of `config`.
+pub(crate) fn check_fragments_per_message(
+    config: &AssemblerConfig,
+    total_n_fragments: u64,
+) -> Result<(), AssemblerError> {
+    if total_n_fragments > config.max_fragments_per_message {
+        return Err(AssemblerError::LimitExceeded(Limit::FragmentsPerMessage {
+            limit: config.max_fragments_per_message,
+            requested: total_n_fragments,
+        }));
+    }
+    Ok(())
+}
@@ -0,0 +1,356 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{Display, Formatter};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Instant;
+
+use wg_2024::network::{NodeId, SourceRoutingHeader};
+use wg_2024::packet::{Ack, Fragment, Nack, NackType, Packet};
+
+use crate::clock::{Clock, SystemClock};
+use crate::resend_policy::{DefaultResendPolicy, ResendDecision, ResendPolicy};
+use crate::send_buffer::AckOutcome;
+use crate::windowed_sender::WindowedSender;
+
+/// Identifies an outgoing message, e.g. to show its delivery state next to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MessageHandle {
+    /// Id assigned by the `DeliveryTracker`, unique among its messages.
+    pub message_id: u64,
+    /// Session id of the packets carrying the message.
+    pub session_id: u64,
+}
+
+/// Why an outgoing message could not be delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureReason {
+    /// A fragment was dropped until the resend policy gave up.
+    Dropped,
+    /// The route was broken at the given node.
+    ErrorInRouting(NodeId),
+    /// The route ended at a drone instead of the destination.
+    DestinationIsDrone,
+    /// A fragment reached the given node, which was not the next hop.
+    UnexpectedRecipient(NodeId),
+    /// A fragment was neither acknowledged nor nacked after every retransmission.
+    Timeout,
+    /// The message was cancelled or replaced by another one with the same `session_id`.
+    Cancelled,
+}
+
+impl From<&NackType> for FailureReason {
+    fn from(nack_type: &NackType) -> Self {
+        match nack_type {
+            NackType::Dropped => FailureReason::Dropped,
+            NackType::ErrorInRouting(node) => FailureReason::ErrorInRouting(*node),
+            NackType::DestinationIsDrone => FailureReason::DestinationIsDrone,
+            NackType::UnexpectedRecipient(node) => FailureReason::UnexpectedRecipient(*node),
+        }
+    }
+}
+
+impl Display for FailureReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FailureReason::Dropped => write!(f, "dropped"),
+            FailureReason::ErrorInRouting(node) => write!(f, "route error at node {node}"),
+            FailureReason::DestinationIsDrone => write!(f, "destination is a drone"),
+            FailureReason::UnexpectedRecipient(node) => {
+                write!(f, "unexpected recipient {node}")
+            }
+            FailureReason::Timeout => write!(f, "timed out"),
+            FailureReason::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+/// Delivery state of an outgoing message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryState {
+    /// No fragment has been sent yet.
+    Queued,
+    /// Fragments are being sent, `acknowledged` of them were acknowledged.
+    InFlight {
+        acknowledged: u64,
+        total_n_fragments: u64,
+    },
+    /// Every fragment was acknowledged.
+    Delivered,
+    /// The message cannot be delivered.
+    Failed(FailureReason),
+}
+
+impl DeliveryState {
+    /// Returns `true` if the state never changes anymore.
+    #[must_use]
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, DeliveryState::Delivered | DeliveryState::Failed(_))
+    }
+}
+
+impl Display for DeliveryState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeliveryState::Queued => write!(f, "queued"),
+            DeliveryState::InFlight {
+                acknowledged,
+                total_n_fragments,
+            } => write!(f, "sent ({acknowledged}/{total_n_fragments})"),
+            DeliveryState::Delivered => write!(f, "delivered"),
+            DeliveryState::Failed(reason) => write!(f, "failed: {reason}"),
+        }
+    }
+}
+
+/// State transition of an outgoing message, see `DeliveryTracker::subscribe`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeliveryEvent {
+    pub handle: MessageHandle,
+    pub state: DeliveryState,
+}
+
+#[derive(Debug, Clone)]
+struct TrackedMessage {
+    session_id: u64,
+    total_n_fragments: u64,
+    acknowledged: u64,
+    state: DeliveryState,
+}
+
+/// Sends outgoing messages through a `WindowedSender` and reports the delivery
+/// state of each of them.
+///
+/// States can be queried with `state` or received as `DeliveryEvent`s from the
+/// channels returned by `subscribe`. States of messages that reached a terminal
+/// state are kept until they are `forget`-ed.
+#[derive(Debug, Default)]
+pub struct DeliveryTracker<P = DefaultResendPolicy, C = SystemClock> {
+    sender: WindowedSender<P, C>,
+    messages: HashMap<u64, TrackedMessage>,
+    /// Message id of the message sent with each active `session_id`.
+    sessions: HashMap<u64, u64>,
+    next_message_id: u64,
+    subscribers: Vec<Sender<DeliveryEvent>>,
+}
+
+impl DeliveryTracker {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<P: ResendPolicy, C: Clock> DeliveryTracker<P, C> {
+    /// Creates a tracker sending its messages with the given sender.
+    #[must_use]
+    pub fn with_sender(sender: WindowedSender<P, C>) -> Self {
+        DeliveryTracker {
+            sender,
+            messages: HashMap::new(),
+            sessions: HashMap::new(),
+            next_message_id: 0,
+            subscribers: Vec::new(),
+        }
+    }
+
+    /// Returns a channel receiving every state transition from now on.
+    pub fn subscribe(&mut self) -> Receiver<DeliveryEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    /// Queues the fragments of a message to be sent with `session_id` along
+    /// `routing_header`, see `WindowedSender::insert`. A message still being sent
+    /// with the same `session_id` fails with `FailureReason::Cancelled`.
+    ///
+    /// # Returns
+    /// Returns the handle of the message, whose state is `DeliveryState::Queued`.
+    pub fn send(
+        &mut self,
+        session_id: u64,
+        fragments: Vec<Fragment>,
+        routing_header: &SourceRoutingHeader,
+    ) -> MessageHandle {
+        if let Some(message_id) = self.sessions.remove(&session_id) {
+            self.transition(message_id, DeliveryState::Failed(FailureReason::Cancelled));
+        }
+        let message_id = self.next_message_id;
+        self.next_message_id += 1;
+        self.messages.insert(
+            message_id,
+            TrackedMessage {
+                session_id,
+                total_n_fragments: fragments.len() as u64,
+                acknowledged: 0,
+                state: DeliveryState::Queued,
+            },
+        );
+        self.sessions.insert(session_id, message_id);
+        self.sender.insert(session_id, fragments, routing_header);
+        self.notify(message_id);
+        MessageHandle {
+            message_id,
+            session_id,
+        }
+    }
+
+    /// Stops sending a message, which fails with `FailureReason::Cancelled`.
+    ///
+    /// # Returns
+    /// Returns `false` if the message is unknown or already in a terminal state.
+    pub fn cancel(&mut self, handle: MessageHandle) -> bool {
+        if self.sessions.get(&handle.session_id) != Some(&handle.message_id) {
+            return false;
+        }
+        self.fail(handle.session_id, FailureReason::Cancelled);
+        true
+    }
+
+    /// Handles an `Ack`, see `WindowedSender::handle_ack`. The message becomes
+    /// `DeliveryState::Delivered` once every fragment is acknowledged.
+    pub fn handle_ack(&mut self, session_id: u64, ack: &Ack) -> AckOutcome {
+        let outcome = self.sender.handle_ack(session_id, ack);
+        let Some(&message_id) = self.sessions.get(&session_id) else {
+            return outcome;
+        };
+        match outcome {
+            AckOutcome::Pending => {
+                if let Some(message) = self.messages.get_mut(&message_id) {
+                    message.acknowledged += 1;
+                    let state = DeliveryState::InFlight {
+                        acknowledged: message.acknowledged,
+                        total_n_fragments: message.total_n_fragments,
+                    };
+                    self.transition(message_id, state);
+                }
+            }
+            AckOutcome::Completed => {
+                self.sessions.remove(&session_id);
+                self.transition(message_id, DeliveryState::Delivered);
+            }
+            AckOutcome::Ignored => {}
+        }
+        outcome
+    }
+
+    /// Handles a `Nack`, see `WindowedSender::handle_nack`. The message fails
+    /// with the reason of the `Nack` if the resend policy gives up.
+    pub fn handle_nack(&mut self, session_id: u64, nack: &Nack) -> Option<ResendDecision> {
+        let decision = self.sender.handle_nack(session_id, nack)?;
+        if decision == ResendDecision::GiveUp {
+            self.fail(session_id, FailureReason::from(&nack.nack_type));
+        }
+        Some(decision)
+    }
+
+    /// Replaces the route of a message, see `WindowedSender::set_route`.
+    pub fn set_route(&mut self, session_id: u64, routing_header: &SourceRoutingHeader) -> bool {
+        self.sender.set_route(session_id, routing_header)
+    }
+
+    /// Collects the packets to be sent now, see `WindowedSender::poll_next_packets`.
+    /// Queued messages whose first fragment is sent become in flight, and
+    /// messages whose fragments ran out of retransmissions fail with
+    /// `FailureReason::Timeout`.
+    pub fn poll_next_packets(&mut self) -> Vec<Packet> {
+        let packets = self.sender.poll_next_packets();
+
+        let sent: BTreeSet<u64> = packets.iter().map(|packet| packet.session_id).collect();
+        for session_id in sent {
+            let Some(&message_id) = self.sessions.get(&session_id) else {
+                continue;
+            };
+            if let Some(message) = self.messages.get(&message_id) {
+                if message.state == DeliveryState::Queued {
+                    let state = DeliveryState::InFlight {
+                        acknowledged: 0,
+                        total_n_fragments: message.total_n_fragments,
+                    };
+                    self.transition(message_id, state);
+                }
+            }
+        }
+
+        let mut timed_out: Vec<u64> = self
+            .sessions
+            .keys()
+            .copied()
+            .filter(|session_id| self.sender.is_given_up(*session_id))
+            .collect();
+        timed_out.sort_unstable();
+        for session_id in timed_out {
+            self.fail(session_id, FailureReason::Timeout);
+        }
+        packets
+    }
+
+    /// Returns the earliest time at which `poll_next_packets` must be called to
+    /// retransmit fragments, see `WindowedSender::next_timeout`.
+    #[must_use]
+    pub fn next_timeout(&self) -> Option<Instant> {
+        self.sender.next_timeout()
+    }
+
+    /// Returns the delivery state of a message, or `None` if it is unknown or was
+    /// forgotten.
+    #[must_use]
+    pub fn state(&self, handle: MessageHandle) -> Option<DeliveryState> {
+        self.messages
+            .get(&handle.message_id)
+            .filter(|message| message.session_id == handle.session_id)
+            .map(|message| message.state)
+    }
+
+    /// Forgets a message in a terminal state.
+    ///
+    /// # Returns
+    /// Returns the last state of the message, or `None` if it is unknown or still
+    /// being sent.
+    pub fn forget(&mut self, handle: MessageHandle) -> Option<DeliveryState> {
+        let state = self.state(handle).filter(DeliveryState::is_terminal)?;
+        self.messages.remove(&handle.message_id);
+        Some(state)
+    }
+
+    /// Returns the sender of the messages.
+    #[must_use]
+    pub fn sender(&self) -> &WindowedSender<P, C> {
+        &self.sender
+    }
+
+    /// Stops sending the message of `session_id` and marks it as failed.
+    fn fail(&mut self, session_id: u64, reason: FailureReason) {
+        self.sender.remove(session_id);
+        if let Some(message_id) = self.sessions.remove(&session_id) {
+            self.transition(message_id, DeliveryState::Failed(reason));
+        }
+    }
+
+    fn transition(&mut self, message_id: u64, state: DeliveryState) {
+        let Some(message) = self.messages.get_mut(&message_id) else {
+            return;
+        };
+        if message.state == state {
+            return;
+        }
+        message.state = state;
+        self.notify(message_id);
+    }
+
+    /// Sends the current state of a message to every subscriber, dropping the
+    /// subscribers whose receiver was dropped.
+    fn notify(&mut self, message_id: u64) {
+        let Some(message) = self.messages.get(&message_id) else {
+            return;
+        };
+        let event = DeliveryEvent {
+            handle: MessageHandle {
+                message_id,
+                session_id: message.session_id,
+            },
+            state: message.state,
+        };
+        self.subscribers
+            .retain(|subscriber| subscriber.send(event).is_ok());
+    }
+}
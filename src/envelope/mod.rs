@@ -0,0 +1,179 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use messages::{ChatRequest, ChatResponse, MediaRequest, MediaResponse, TextRequest, TextResponse};
+
+/// Magic bytes every envelope starts with.
+pub const MAGIC: [u8; 2] = *b"NP";
+/// Version of the envelope layout produced by `wrap`.
+pub const VERSION: u8 = 1;
+/// Size of the header prepended to the payload: magic bytes, version,
+/// kind tag and big-endian `u64` payload length.
+pub const HEADER_SIZE: usize = MAGIC.len() + 1 + 1 + 8;
+
+/// Kind of message carried by an envelope, so that receivers can dispatch it
+/// to the right handler without trying to parse it as every type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageKind {
+    TextRequest,
+    TextResponse,
+    MediaRequest,
+    MediaResponse,
+    ChatRequest,
+    ChatResponse,
+    /// Payload that is not one of the messages above.
+    Raw,
+}
+
+impl MessageKind {
+    /// Returns the tag identifying the kind on the wire.
+    #[must_use]
+    pub fn tag(self) -> u8 {
+        match self {
+            MessageKind::TextRequest => 1,
+            MessageKind::TextResponse => 2,
+            MessageKind::MediaRequest => 3,
+            MessageKind::MediaResponse => 4,
+            MessageKind::ChatRequest => 5,
+            MessageKind::ChatResponse => 6,
+            MessageKind::Raw => 255,
+        }
+    }
+
+    /// Returns the kind identified by `tag`, if any.
+    #[must_use]
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            1 => Some(MessageKind::TextRequest),
+            2 => Some(MessageKind::TextResponse),
+            3 => Some(MessageKind::MediaRequest),
+            4 => Some(MessageKind::MediaResponse),
+            5 => Some(MessageKind::ChatRequest),
+            6 => Some(MessageKind::ChatResponse),
+            255 => Some(MessageKind::Raw),
+            _ => None,
+        }
+    }
+}
+
+/// Message types with a fixed `MessageKind`.
+pub trait Tagged {
+    const KIND: MessageKind;
+}
+
+impl Tagged for TextRequest {
+    const KIND: MessageKind = MessageKind::TextRequest;
+}
+
+impl Tagged for TextResponse {
+    const KIND: MessageKind = MessageKind::TextResponse;
+}
+
+impl Tagged for MediaRequest {
+    const KIND: MessageKind = MessageKind::MediaRequest;
+}
+
+impl Tagged for MediaResponse {
+    const KIND: MessageKind = MessageKind::MediaResponse;
+}
+
+impl Tagged for ChatRequest {
+    const KIND: MessageKind = MessageKind::ChatRequest;
+}
+
+impl Tagged for ChatResponse {
+    const KIND: MessageKind = MessageKind::ChatResponse;
+}
+
+/// Reasons for rejecting an envelope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnvelopeError {
+    /// The bytes are shorter than the envelope header.
+    TooShort(usize),
+    /// The bytes do not start with `MAGIC`, so they come from a foreign sender.
+    BadMagic([u8; 2]),
+    UnsupportedVersion(u8),
+    UnknownKind(u8),
+    /// The envelope carries a different kind of message than expected.
+    UnexpectedKind {
+        expected: MessageKind,
+        found: MessageKind,
+    },
+    /// The payload length in the header does not match the bytes received.
+    LengthMismatch {
+        declared: u64,
+        actual: u64,
+    },
+}
+
+impl Display for EnvelopeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnvelopeError::TooShort(length) => {
+                write!(f, "{length} bytes are too short for an envelope header")
+            }
+            EnvelopeError::BadMagic(magic) => write!(f, "unexpected magic bytes {magic:?}"),
+            EnvelopeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported envelope version {version}")
+            }
+            EnvelopeError::UnknownKind(tag) => write!(f, "unknown message kind tag {tag}"),
+            EnvelopeError::UnexpectedKind { expected, found } => {
+                write!(f, "expected a {expected:?} envelope, found {found:?}")
+            }
+            EnvelopeError::LengthMismatch { declared, actual } => write!(
+                f,
+                "envelope declares {declared} payload bytes but carries {actual}"
+            ),
+        }
+    }
+}
+
+impl Error for EnvelopeError {}
+
+/// Prepends an envelope header describing `payload` as a message of the given kind.
+#[must_use]
+pub fn wrap(kind: MessageKind, payload: &[u8]) -> Vec<u8> {
+    let mut byte_vector = Vec::with_capacity(HEADER_SIZE + payload.len());
+    byte_vector.extend_from_slice(&MAGIC);
+    byte_vector.push(VERSION);
+    byte_vector.push(kind.tag());
+    byte_vector.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+    byte_vector.extend_from_slice(payload);
+    byte_vector
+}
+
+/// Checks the envelope header at the start of `byte_vector`.
+///
+/// # Returns
+/// Returns the kind of the message and the payload following the header.
+///
+/// # Errors
+///
+/// Returns an `EnvelopeError` if the header is truncated, foreign, of an
+/// unsupported version or kind, or if the payload has the wrong length.
+pub fn unwrap(byte_vector: &[u8]) -> Result<(MessageKind, &[u8]), EnvelopeError> {
+    if byte_vector.len() < HEADER_SIZE {
+        return Err(EnvelopeError::TooShort(byte_vector.len()));
+    }
+    let (header, payload) = byte_vector.split_at(HEADER_SIZE);
+
+    let magic = [header[0], header[1]];
+    if magic != MAGIC {
+        return Err(EnvelopeError::BadMagic(magic));
+    }
+    if header[2] != VERSION {
+        return Err(EnvelopeError::UnsupportedVersion(header[2]));
+    }
+    let kind = MessageKind::from_tag(header[3]).ok_or(EnvelopeError::UnknownKind(header[3]))?;
+
+    let mut length = [0; 8];
+    length.copy_from_slice(&header[4..]);
+    let declared = u64::from_be_bytes(length);
+    if declared != payload.len() as u64 {
+        return Err(EnvelopeError::LengthMismatch {
+            declared,
+            actual: payload.len() as u64,
+        });
+    }
+    Ok((kind, payload))
+}
@@ -0,0 +1,171 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::string::FromUtf8Error;
+
+use crate::compression::CompressionError;
+use crate::config::Limit;
+use crate::envelope::EnvelopeError;
+use crate::fec::FecError;
+
+/// Errors that can occur while fragmentizing or reassembling data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssemblerError {
+    /// No fragments were provided. An empty payload is carried by a single
+    /// fragment with `length: 0`, not by an empty slice.
+    Empty,
+    /// More than one fragment carries the same `fragment_index`.
+    DuplicateIndex(u64),
+    /// A fragment carries the same `fragment_index` as an earlier one but
+    /// different data.
+    ConflictingDuplicate { fragment_index: u64 },
+    /// A fragment index within `0..total_n_fragments` was never provided.
+    MissingIndex(u64),
+    /// A fragment index is not lower than its `total_n_fragments`.
+    IndexOutOfRange {
+        fragment_index: u64,
+        total_n_fragments: u64,
+    },
+    /// A fragment disagrees with the others on `total_n_fragments`.
+    InconsistentTotal {
+        fragment_index: u64,
+        expected: u64,
+        found: u64,
+    },
+    /// A fragment declares a `length` that does not fit into its data array.
+    InvalidLength { fragment_index: u64, length: u8 },
+    /// A fragment other than the last one is not full, or the last fragment of
+    /// a multi-fragment message is empty.
+    ShortFragment { fragment_index: u64, length: u8 },
+    /// The bytes of a fragment after its `length` are not zero.
+    NonZeroPadding { fragment_index: u64 },
+    /// The reassembled bytes are not valid UTF-8 and cannot hold a JSON message.
+    InvalidUtf8(FromUtf8Error),
+    /// The reassembled JSON could not be parsed into the requested message type.
+    Deserialization(String),
+    /// The reassembled bytes do not start with a valid envelope.
+    Envelope(EnvelopeError),
+    /// The reassembled bytes are not a valid compressed payload.
+    Compression(CompressionError),
+    /// The fragments carrying forward error correction cannot be decoded.
+    Fec(FecError),
+    /// The reassembled bytes are too short to carry a checksum trailer.
+    MissingChecksum { session_id: Option<u64> },
+    /// The checksum trailer does not match the reassembled payload.
+    ChecksumMismatch {
+        session_id: Option<u64>,
+        expected: u32,
+        actual: u32,
+    },
+    /// A packet carries no hops, so its source is unknown.
+    EmptyRoute,
+    /// Accepting a fragment would exceed a limit of the `AssemblerConfig`.
+    LimitExceeded(Limit),
+    /// Writing the reassembled bytes to a sink failed.
+    Io(io::ErrorKind),
+}
+
+impl Display for AssemblerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssemblerError::Empty => write!(f, "no data to process"),
+            AssemblerError::DuplicateIndex(index) => {
+                write!(f, "duplicate fragment with index {index}")
+            }
+            AssemblerError::ConflictingDuplicate { fragment_index } => write!(
+                f,
+                "duplicate fragment with index {fragment_index} carries different data"
+            ),
+            AssemblerError::MissingIndex(index) => {
+                write!(f, "missing fragment with index {index}")
+            }
+            AssemblerError::IndexOutOfRange {
+                fragment_index,
+                total_n_fragments,
+            } => write!(
+                f,
+                "fragment index {fragment_index} is out of range for {total_n_fragments} fragments"
+            ),
+            AssemblerError::InconsistentTotal {
+                fragment_index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "fragment {fragment_index} declares {found} total fragments, expected {expected}"
+            ),
+            AssemblerError::InvalidLength {
+                fragment_index,
+                length,
+            } => write!(f, "fragment {fragment_index} has invalid length {length}"),
+            AssemblerError::ShortFragment {
+                fragment_index,
+                length,
+            } => write!(
+                f,
+                "fragment {fragment_index} carries only {length} bytes but is not the last one"
+            ),
+            AssemblerError::NonZeroPadding { fragment_index } => {
+                write!(f, "fragment {fragment_index} has non-zero padding")
+            }
+            AssemblerError::InvalidUtf8(error) => write!(f, "message is not valid UTF-8: {error}"),
+            AssemblerError::Deserialization(error) => {
+                write!(f, "message cannot be deserialized: {error}")
+            }
+            AssemblerError::Envelope(error) => write!(f, "invalid envelope: {error}"),
+            AssemblerError::Compression(error) => write!(f, "invalid compressed payload: {error}"),
+            AssemblerError::Fec(error) => write!(f, "cannot correct errors: {error}"),
+            AssemblerError::MissingChecksum { session_id } => {
+                write!(f, "message")?;
+                if let Some(session_id) = session_id {
+                    write!(f, " of session {session_id}")?;
+                }
+                write!(f, " is too short to carry a checksum")
+            }
+            AssemblerError::ChecksumMismatch {
+                session_id,
+                expected,
+                actual,
+            } => {
+                write!(f, "checksum mismatch")?;
+                if let Some(session_id) = session_id {
+                    write!(f, " in session {session_id}")?;
+                }
+                write!(f, ": expected {expected:#010x}, computed {actual:#010x}")
+            }
+            AssemblerError::EmptyRoute => write!(f, "packet has an empty routing header"),
+            AssemblerError::LimitExceeded(limit) => write!(f, "limit exceeded: {limit}"),
+            AssemblerError::Io(kind) => write!(f, "cannot write message: {kind}"),
+        }
+    }
+}
+
+impl Error for AssemblerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AssemblerError::InvalidUtf8(error) => Some(error),
+            AssemblerError::Envelope(error) => Some(error),
+            AssemblerError::Compression(error) => Some(error),
+            AssemblerError::Fec(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<EnvelopeError> for AssemblerError {
+    fn from(error: EnvelopeError) -> Self {
+        AssemblerError::Envelope(error)
+    }
+}
+
+impl From<CompressionError> for AssemblerError {
+    fn from(error: CompressionError) -> Self {
+        AssemblerError::Compression(error)
+    }
+}
+
+impl From<FecError> for AssemblerError {
+    fn from(error: FecError) -> Self {
+        AssemblerError::Fec(error)
+    }
+}
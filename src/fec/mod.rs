@@ -0,0 +1,343 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use wg_2024::packet::{Fragment, FRAGMENT_DSIZE};
+
+use crate::naive_assembler::{check_fragment, NaiveAssembler};
+use crate::stateful_assembler::StatefulAssembler;
+use crate::{Assembler, AssemblerError};
+
+/// Size of the header prepended to the payload before it is split into data
+/// fragments: its big-endian `u64` length, as data fragments are padded.
+const LENGTH_HEADER_SIZE: usize = 8;
+
+/// Exponentials and logarithms of GF(2^8) with the polynomial 0x11D. The
+/// exponentials are repeated so that the sum of two logarithms can index them.
+const GF_TABLES: ([u8; 512], [u8; 256]) = {
+    let mut exp = [0; 512];
+    let mut log = [0; 256];
+    let mut x: u16 = 1;
+    let mut i = 0;
+    while i < 255 {
+        exp[i] = x as u8;
+        log[x as usize] = i as u8;
+        x <<= 1;
+        if x & 0x100 != 0 {
+            x ^= 0x11D;
+        }
+        i += 1;
+    }
+    while i < 512 {
+        exp[i] = exp[i - 255];
+        i += 1;
+    }
+    (exp, log)
+};
+
+/// Reasons for failing to reassemble fragments produced by a `FecAssembler`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FecError {
+    /// `total_n_fragments` cannot result from the configured block sizes.
+    InvalidLayout { total_n_fragments: u64 },
+    /// Too many fragments of a block were lost to reconstruct its data.
+    Unrecoverable {
+        block: u64,
+        received: usize,
+        required: usize,
+    },
+    /// The length header declares more bytes than the data fragments carry.
+    LengthMismatch { declared: u64, actual: u64 },
+}
+
+impl Display for FecError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FecError::InvalidLayout { total_n_fragments } => write!(
+                f,
+                "{total_n_fragments} fragments do not match the configured blocks"
+            ),
+            FecError::Unrecoverable {
+                block,
+                received,
+                required,
+            } => write!(
+                f,
+                "block {block} has only {received} of the {required} fragments needed"
+            ),
+            FecError::LengthMismatch { declared, actual } => write!(
+                f,
+                "payload declares {declared} bytes but the fragments carry {actual}"
+            ),
+        }
+    }
+}
+
+impl Error for FecError {}
+
+/// `StatefulAssembler` adding forward error correction, so that lost fragments
+/// can be reconstructed without retransmission.
+///
+/// The payload is split into blocks of `data_per_block` data fragments, each
+/// followed by `parity_per_block` Reed-Solomon parity fragments. Any
+/// `parity_per_block` fragments of a block can be lost, `assemble` only needs
+/// as many fragments of each block as it has data fragments. Both ends must use
+/// the same block sizes.
+#[derive(Debug, Clone, Copy)]
+pub struct FecAssembler {
+    data_per_block: usize,
+    parity_per_block: usize,
+}
+
+impl FecAssembler {
+    /// # Panics
+    ///
+    /// Will panic if `data_per_block` is zero, or if a block would have more
+    /// than 256 fragments.
+    #[must_use]
+    pub fn new(data_per_block: usize, parity_per_block: usize) -> Self {
+        assert!(data_per_block > 0, "blocks must carry data");
+        assert!(
+            data_per_block + parity_per_block <= 256,
+            "blocks cannot have more than 256 fragments"
+        );
+        FecAssembler {
+            data_per_block,
+            parity_per_block,
+        }
+    }
+
+    #[must_use]
+    pub fn data_per_block(&self) -> usize {
+        self.data_per_block
+    }
+
+    #[must_use]
+    pub fn parity_per_block(&self) -> usize {
+        self.parity_per_block
+    }
+
+    /// Returns the number of blocks and data fragments of a message of
+    /// `total_n_fragments`, if the configured blocks can produce it.
+    fn layout(&self, total_n_fragments: u64) -> Option<(u64, u64)> {
+        let data_per_block = self.data_per_block as u64;
+        let parity_per_block = self.parity_per_block as u64;
+        let blocks = total_n_fragments.div_ceil(data_per_block + parity_per_block);
+        let data_fragments = total_n_fragments.checked_sub(blocks * parity_per_block)?;
+        let last_block = data_fragments.checked_sub((blocks.checked_sub(1)?) * data_per_block)?;
+        (1..=data_per_block)
+            .contains(&last_block)
+            .then_some((blocks, data_fragments))
+    }
+
+    /// Returns the parity coefficient of data fragment `data` in parity
+    /// fragment `parity`, an entry of a Cauchy matrix so that any set of data
+    /// and parity rows is invertible.
+    fn coefficient(&self, parity: usize, data: usize) -> u8 {
+        // Distinct and nonzero sums, as data < data_per_block <= data_per_block + parity
+        gf_inverse((self.data_per_block + parity) as u8 ^ data as u8)
+    }
+
+    fn try_fragmentize(&self, byte_vector: &[u8]) -> Result<Vec<Fragment>, AssemblerError> {
+        let mut payload = Vec::with_capacity(LENGTH_HEADER_SIZE + byte_vector.len());
+        payload.extend_from_slice(&(byte_vector.len() as u64).to_be_bytes());
+        payload.extend_from_slice(byte_vector);
+        let data = NaiveAssembler::try_disassemble(&payload)?;
+
+        let blocks = data.chunks(self.data_per_block);
+        let total_n_fragments = (data.len() + blocks.len() * self.parity_per_block) as u64;
+        let mut fragments = Vec::with_capacity(total_n_fragments as usize);
+        for block in blocks {
+            let mut parity = vec![[0; FRAGMENT_DSIZE]; self.parity_per_block];
+            for (index, fragment) in block.iter().enumerate() {
+                for (row, parity) in parity.iter_mut().enumerate() {
+                    gf_add_scaled(parity, &fragment.data, self.coefficient(row, index));
+                }
+                fragments.push(fragment.data);
+            }
+            fragments.extend(parity);
+        }
+
+        Ok(fragments
+            .into_iter()
+            .zip(0..)
+            .map(|(data, fragment_index)| Fragment {
+                fragment_index,
+                total_n_fragments,
+                length: FRAGMENT_DSIZE as u8,
+                data,
+            })
+            .collect())
+    }
+
+    fn try_assemble(&self, fragments: &[Fragment]) -> Result<Vec<u8>, AssemblerError> {
+        let total_n_fragments = fragments
+            .first()
+            .ok_or(AssemblerError::Empty)?
+            .total_n_fragments;
+        let (blocks, data_fragments) = self
+            .layout(total_n_fragments)
+            .ok_or(FecError::InvalidLayout { total_n_fragments })?;
+
+        // Fragments of every block received, with their position in the block
+        let mut received: BTreeMap<u64, Vec<(usize, &[u8; FRAGMENT_DSIZE])>> = BTreeMap::new();
+        let block_size = (self.data_per_block + self.parity_per_block) as u64;
+        for fragment in fragments {
+            check_fragment(fragment, total_n_fragments)?;
+            let block = received
+                .entry(fragment.fragment_index / block_size)
+                .or_default();
+            let position = (fragment.fragment_index % block_size) as usize;
+            if block.iter().any(|(other, _)| *other == position) {
+                return Err(AssemblerError::DuplicateIndex(fragment.fragment_index));
+            }
+            block.push((position, &fragment.data));
+        }
+
+        let mut payload = Vec::with_capacity(fragments.len() * FRAGMENT_DSIZE);
+        for block in 0..blocks {
+            let mut received = received.remove(&block).unwrap_or_default();
+            let data_in_block = (data_fragments - block * self.data_per_block as u64)
+                .min(self.data_per_block as u64) as usize;
+            if received.len() < data_in_block {
+                return Err(FecError::Unrecoverable {
+                    block,
+                    received: received.len(),
+                    required: data_in_block,
+                }
+                .into());
+            }
+            // Data fragments first, so that parity is only used for the gaps
+            received.sort_unstable_by_key(|(position, _)| *position);
+            received.truncate(data_in_block);
+            for data in self.recover_block(data_in_block, &received) {
+                payload.extend_from_slice(&data);
+            }
+        }
+
+        // At least one data fragment was recovered, so the header is complete
+        let (length, payload) = payload.split_at(LENGTH_HEADER_SIZE);
+        let declared = u64::from_be_bytes(length.try_into().unwrap());
+        if declared > payload.len() as u64 {
+            return Err(FecError::LengthMismatch {
+                declared,
+                actual: payload.len() as u64,
+            }
+            .into());
+        }
+        Ok(payload[..declared as usize].to_vec())
+    }
+
+    /// Reconstructs the data fragments of a block from exactly `data_in_block`
+    /// of its fragments, sorted by position.
+    fn recover_block(
+        &self,
+        data_in_block: usize,
+        received: &[(usize, &[u8; FRAGMENT_DSIZE])],
+    ) -> Vec<[u8; FRAGMENT_DSIZE]> {
+        if received
+            .last()
+            .is_none_or(|(position, _)| *position < data_in_block)
+        {
+            return received.iter().map(|(_, data)| **data).collect();
+        }
+
+        // Row of the encoding matrix of every received fragment: a unit row for
+        // data fragments, Cauchy coefficients for parity fragments
+        let rows: Vec<Vec<u8>> = received
+            .iter()
+            .map(|(position, _)| {
+                (0..data_in_block)
+                    .map(|data| match position.checked_sub(data_in_block) {
+                        None => u8::from(*position == data),
+                        Some(parity) => self.coefficient(parity, data),
+                    })
+                    .collect()
+            })
+            .collect();
+        let decoding = gf_invert(rows);
+
+        decoding
+            .iter()
+            .map(|row| {
+                let mut data = [0; FRAGMENT_DSIZE];
+                for (coefficient, (_, fragment)) in row.iter().zip(received) {
+                    gf_add_scaled(&mut data, fragment, *coefficient);
+                }
+                data
+            })
+            .collect()
+    }
+}
+
+impl StatefulAssembler for FecAssembler {
+    fn fragmentize(&mut self, byte_vector: &[u8]) -> Result<Vec<Fragment>, AssemblerError> {
+        self.try_fragmentize(byte_vector)
+    }
+
+    /// Reassembles the payload from the fragments received, which may miss up
+    /// to `parity_per_block` fragments of every block.
+    fn assemble(&mut self, fragments: &[Fragment]) -> Result<Vec<u8>, AssemblerError> {
+        self.try_assemble(fragments)
+    }
+}
+
+fn gf_multiply(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    let (exp, log) = &GF_TABLES;
+    exp[log[a as usize] as usize + log[b as usize] as usize]
+}
+
+fn gf_inverse(a: u8) -> u8 {
+    let (exp, log) = &GF_TABLES;
+    exp[255 - log[a as usize] as usize]
+}
+
+/// Adds `coefficient * source` to `target`, byte by byte.
+fn gf_add_scaled(
+    target: &mut [u8; FRAGMENT_DSIZE],
+    source: &[u8; FRAGMENT_DSIZE],
+    coefficient: u8,
+) {
+    if coefficient == 0 {
+        return;
+    }
+    for (target, source) in target.iter_mut().zip(source) {
+        *target ^= gf_multiply(*source, coefficient);
+    }
+}
+
+/// Inverts a square matrix by Gauss-Jordan elimination. The rows of the
+/// encoding matrix are always linearly independent, so a pivot always exists.
+fn gf_invert(mut matrix: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+    let size = matrix.len();
+    let mut inverse: Vec<Vec<u8>> = (0..size)
+        .map(|row| (0..size).map(|column| u8::from(row == column)).collect())
+        .collect();
+
+    for column in 0..size {
+        let pivot = (column..size)
+            .find(|row| matrix[*row][column] != 0)
+            .expect("encoding rows are linearly independent");
+        matrix.swap(column, pivot);
+        inverse.swap(column, pivot);
+
+        let scale = gf_inverse(matrix[column][column]);
+        for value in matrix[column].iter_mut().chain(inverse[column].iter_mut()) {
+            *value = gf_multiply(*value, scale);
+        }
+        for row in 0..size {
+            let factor = matrix[row][column];
+            if row == column || factor == 0 {
+                continue;
+            }
+            for index in 0..size {
+                matrix[row][index] ^= gf_multiply(factor, matrix[column][index]);
+                inverse[row][index] ^= gf_multiply(factor, inverse[column][index]);
+            }
+        }
+    }
+    inverse
+}
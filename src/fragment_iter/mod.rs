@@ -0,0 +1,126 @@
+use std::io::{self, Read};
+use std::iter::FusedIterator;
+
+use wg_2024::packet::{Fragment, FRAGMENT_DSIZE};
+
+/// Iterator producing the fragments of a byte slice on demand, see `disassemble_iter`.
+#[derive(Debug, Clone)]
+pub struct FragmentIter<'a> {
+    byte_vector: &'a [u8],
+    next_index: u64,
+    total_n_fragments: u64,
+}
+
+/// Fragmentizes a byte slice lazily, producing the same fragments as
+/// `NaiveAssembler::disassemble` one at a time.
+#[must_use]
+pub fn disassemble_iter(byte_vector: &[u8]) -> FragmentIter<'_> {
+    FragmentIter {
+        byte_vector,
+        next_index: 0,
+        total_n_fragments: total_n_fragments(byte_vector.len() as u64),
+    }
+}
+
+impl Iterator for FragmentIter<'_> {
+    type Item = Fragment;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_index >= self.total_n_fragments {
+            return None;
+        }
+        let offset = self.next_index as usize * FRAGMENT_DSIZE;
+        let end = self.byte_vector.len().min(offset + FRAGMENT_DSIZE);
+        let fragment = new_fragment(
+            self.next_index,
+            self.total_n_fragments,
+            &self.byte_vector[offset..end],
+        );
+        self.next_index += 1;
+        Some(fragment)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.total_n_fragments - self.next_index) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for FragmentIter<'_> {}
+
+impl FusedIterator for FragmentIter<'_> {}
+
+/// Iterator producing the fragments of a payload read from a `Read`, see
+/// `disassemble_reader`.
+#[derive(Debug)]
+pub struct ReaderFragments<R> {
+    reader: R,
+    length: u64,
+    next_index: u64,
+    total_n_fragments: u64,
+}
+
+/// Fragmentizes `length` bytes pulled from `reader`, reading a single fragment's
+/// worth of data each time the next fragment is requested.
+///
+/// The iterator stops after the first error, e.g. if the reader ends before
+/// `length` bytes were read.
+pub fn disassemble_reader<R: Read>(reader: R, length: u64) -> ReaderFragments<R> {
+    ReaderFragments {
+        reader,
+        length,
+        next_index: 0,
+        total_n_fragments: total_n_fragments(length),
+    }
+}
+
+impl<R: Read> Iterator for ReaderFragments<R> {
+    type Item = io::Result<Fragment>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_index >= self.total_n_fragments {
+            return None;
+        }
+        let offset = self.next_index * FRAGMENT_DSIZE as u64;
+        let chunk_length = (self.length - offset).min(FRAGMENT_DSIZE as u64) as usize;
+
+        let mut chunk = [0; FRAGMENT_DSIZE];
+        if let Err(error) = self.reader.read_exact(&mut chunk[..chunk_length]) {
+            // Stop at the first error
+            self.next_index = self.total_n_fragments;
+            return Some(Err(error));
+        }
+        let fragment = new_fragment(
+            self.next_index,
+            self.total_n_fragments,
+            &chunk[..chunk_length],
+        );
+        self.next_index += 1;
+        Some(Ok(fragment))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // An error ends the iteration early, so only one more item is certain
+        let remaining = usize::try_from(self.total_n_fragments - self.next_index).ok();
+        (remaining.map_or(1, |remaining| remaining.min(1)), remaining)
+    }
+}
+
+impl<R: Read> FusedIterator for ReaderFragments<R> {}
+
+/// Returns the number of fragments of a payload of `length` bytes. An empty
+/// payload is still sent as a single fragment without data.
+fn total_n_fragments(length: u64) -> u64 {
+    length.div_ceil(FRAGMENT_DSIZE as u64).max(1)
+}
+
+fn new_fragment(fragment_index: u64, total_n_fragments: u64, chunk: &[u8]) -> Fragment {
+    let mut data = [0; FRAGMENT_DSIZE];
+    data[..chunk.len()].copy_from_slice(chunk);
+    Fragment {
+        fragment_index,
+        total_n_fragments,
+        length: chunk.len() as u8,
+        data,
+    }
+}
@@ -1,8 +1,36 @@
 //! This library provides an Assembler trait with two functions `disassemble` and `reassemble`.
 //! There is a simple struct implementing this trait; `NaiveAssembler`.
-use wg_2024::packet::Fragment;
+use std::collections::BTreeMap;
 
+use config::DuplicatePolicy;
+use envelope::MessageKind;
+use messages::DroneSend;
+use wg_2024::network::{NodeId, SourceRoutingHeader};
+use wg_2024::packet::{Fragment, Packet};
+
+pub mod checksum;
+pub mod clock;
+pub mod compression;
+pub mod config;
+pub mod delivery;
+pub mod envelope;
+pub mod error;
+pub mod fec;
+pub mod fragment_iter;
+pub mod missing_fragments;
 pub mod naive_assembler;
+mod packets;
+pub mod pipeline;
+pub mod reassembly_buffer;
+pub mod resend_policy;
+pub mod rtt;
+pub mod send_buffer;
+pub mod stateful_assembler;
+pub mod streaming_reassembler;
+pub mod validation;
+pub mod windowed_sender;
+
+pub use error::AssemblerError;
 
 pub trait Assembler {
     /// Reassembles data from of fragments (`&[Fragment]`) into a single byte vector.
@@ -12,7 +40,16 @@ pub trait Assembler {
     ///
     /// # Returns
     /// Returns byte vector of the data reassembled from fragments.
-    fn reassemble(fragments: &[Fragment]) -> Vec<u8>;
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the fragments cannot be reassembled, see `try_reassemble`.
+    fn reassemble(fragments: &[Fragment]) -> Vec<u8> {
+        match Self::try_reassemble(fragments) {
+            Ok(byte_vector) => byte_vector,
+            Err(error) => panic!("Fragments cannot be reassembled: {error}"),
+        }
+    }
 
     /// Fragmentizes a byte slice into a vector of fragments.
     ///
@@ -21,5 +58,260 @@ pub trait Assembler {
     ///
     /// # Returns
     /// Returns a fragment vector.
-    fn disassemble(byte_vector: &[u8]) -> Vec<Fragment>;
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the bytes cannot be fragmentized, see `try_disassemble`.
+    fn disassemble(byte_vector: &[u8]) -> Vec<Fragment> {
+        match Self::try_disassemble(byte_vector) {
+            Ok(fragments) => fragments,
+            Err(error) => panic!("Bytes cannot be disassembled: {error}"),
+        }
+    }
+
+    /// Reassembles data from fragments (`&[Fragment]`) into a single byte vector
+    /// without panicking on malformed input.
+    ///
+    /// # Parameters
+    /// - `fragments`: Slice of fragments that are to be reassembled, in any order.
+    ///
+    /// # Returns
+    /// Returns byte vector of the data reassembled from fragments.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AssemblerError` if `fragments` is empty, contains duplicate or
+    /// missing indexes, indexes out of range, inconsistent `total_n_fragments`
+    /// or invalid `length` values.
+    fn try_reassemble(fragments: &[Fragment]) -> Result<Vec<u8>, AssemblerError>;
+
+    /// Reassembles data from fragments (`&[Fragment]`) into a single byte vector,
+    /// treating fragments received more than once according to `policy`.
+    ///
+    /// # Parameters
+    /// - `fragments`: Slice of fragments that are to be reassembled, in any order.
+    /// - `policy`: How duplicate fragment indexes are treated.
+    ///
+    /// # Returns
+    /// Returns byte vector of the data reassembled from fragments.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `try_reassemble` and `validation::deduplicate`.
+    fn try_reassemble_with_policy(
+        fragments: &[Fragment],
+        policy: DuplicatePolicy,
+    ) -> Result<Vec<u8>, AssemblerError> {
+        match policy {
+            DuplicatePolicy::Reject => Self::try_reassemble(fragments),
+            _ => Self::try_reassemble(&validation::deduplicate(fragments, policy)?),
+        }
+    }
+
+    /// Reassembles data from fragments (`&[Fragment]`) into a single byte vector,
+    /// rejecting every protocol violation instead of silently producing
+    /// zero-filled holes.
+    ///
+    /// # Parameters
+    /// - `fragments`: Slice of fragments that are to be reassembled, in any order.
+    ///
+    /// # Returns
+    /// Returns byte vector of the data reassembled from fragments.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `try_reassemble` and `validation::validate_fragments`.
+    fn try_reassemble_strict(fragments: &[Fragment]) -> Result<Vec<u8>, AssemblerError> {
+        validation::validate_fragments(fragments)?;
+        Self::try_reassemble(fragments)
+    }
+
+    /// Fragmentizes a byte slice into a vector of fragments without panicking.
+    ///
+    /// # Parameters
+    /// - `byte_vector`: Bytes to be fragmentized.
+    ///
+    /// # Returns
+    /// Returns a fragment vector.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AssemblerError` if `byte_vector` cannot be fragmentized.
+    fn try_disassemble(byte_vector: &[u8]) -> Result<Vec<Fragment>, AssemblerError>;
+
+    /// Fragmentizes a byte slice into `MsgFragment` packets ready to be sent.
+    ///
+    /// # Parameters
+    /// - `byte_vector`: Bytes to be fragmentized.
+    /// - `routing_header`: Route to the destination, starting with the sending node.
+    ///   Its `hop_index` is set to 1 in every packet.
+    /// - `session_id`: Session id shared by all the packets of the message.
+    ///
+    /// # Returns
+    /// Returns a packet vector, one packet per fragment.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the bytes cannot be fragmentized, see `try_disassemble`.
+    fn disassemble_to_packets(
+        byte_vector: &[u8],
+        routing_header: &SourceRoutingHeader,
+        session_id: u64,
+    ) -> Vec<Packet> {
+        packets::fragments_to_packets(Self::disassemble(byte_vector), routing_header, session_id)
+    }
+
+    /// Reassembles the messages carried by the `MsgFragment` packets of a slice.
+    /// Packets of any other type are ignored.
+    ///
+    /// # Parameters
+    /// - `packets`: Slice of packets, possibly of several sources and sessions and in
+    ///   any order. The source of a packet is the first hop of its route; packets
+    ///   with an empty route are ignored as well.
+    ///
+    /// # Returns
+    /// Returns the result of reassembling each session, keyed by source and `session_id`.
+    fn reassemble_packets(
+        packets: &[Packet],
+    ) -> BTreeMap<(NodeId, u64), Result<Vec<u8>, AssemblerError>> {
+        packets::fragments_by_session(packets)
+            .into_iter()
+            .map(|(key, fragments)| (key, Self::try_reassemble(&fragments)))
+            .collect()
+    }
+
+    /// Serializes a message and fragmentizes its JSON representation.
+    ///
+    /// # Parameters
+    /// - `message`: Message to be sent, e.g. a `TextRequest` or a `MediaResponse`.
+    ///
+    /// # Returns
+    /// Returns a fragment vector.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the bytes cannot be fragmentized, see `try_disassemble`.
+    fn disassemble_message<T: DroneSend>(message: &T) -> Vec<Fragment> {
+        Self::disassemble(message.stringify().as_bytes())
+    }
+
+    /// Reassembles fragments and deserializes them into a message.
+    ///
+    /// # Parameters
+    /// - `fragments`: Slice of fragments that are to be reassembled.
+    ///
+    /// # Returns
+    /// Returns the message carried by the fragments.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `try_reassemble` if the fragments are malformed,
+    /// `AssemblerError::InvalidUtf8` if the reassembled bytes are not UTF-8 and
+    /// `AssemblerError::Deserialization` if they are not a JSON encoded `T`.
+    fn reassemble_message<T: DroneSend>(fragments: &[Fragment]) -> Result<T, AssemblerError> {
+        let byte_vector = Self::try_reassemble(fragments)?;
+        let json = String::from_utf8(byte_vector).map_err(AssemblerError::InvalidUtf8)?;
+        T::from_string(json).map_err(|error| AssemblerError::Deserialization(error.to_string()))
+    }
+
+    /// Fragmentizes a byte slice preceded by an envelope header naming its kind.
+    ///
+    /// # Parameters
+    /// - `kind`: Kind of the message, e.g. `TextRequest::KIND`.
+    /// - `byte_vector`: Bytes to be fragmentized.
+    ///
+    /// # Returns
+    /// Returns a fragment vector.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the bytes cannot be fragmentized, see `try_disassemble`.
+    fn disassemble_enveloped(kind: MessageKind, byte_vector: &[u8]) -> Vec<Fragment> {
+        Self::disassemble(&envelope::wrap(kind, byte_vector))
+    }
+
+    /// Reassembles fragments produced by `disassemble_enveloped`.
+    ///
+    /// # Parameters
+    /// - `fragments`: Slice of fragments that are to be reassembled.
+    ///
+    /// # Returns
+    /// Returns the kind of the message and its bytes, without the envelope header.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `try_reassemble` if the fragments are malformed, or
+    /// `AssemblerError::Envelope` if the bytes do not start with a valid envelope.
+    fn reassemble_enveloped(
+        fragments: &[Fragment],
+    ) -> Result<(MessageKind, Vec<u8>), AssemblerError> {
+        let byte_vector = Self::try_reassemble(fragments)?;
+        let (kind, payload) = envelope::unwrap(&byte_vector)?;
+        Ok((kind, payload.to_vec()))
+    }
+
+    /// Fragmentizes a byte slice followed by a CRC-32 trailer, so that the receiver
+    /// can detect corrupted fragments.
+    ///
+    /// # Parameters
+    /// - `byte_vector`: Bytes to be fragmentized.
+    ///
+    /// # Returns
+    /// Returns a fragment vector.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the bytes cannot be fragmentized, see `try_disassemble`.
+    fn disassemble_checked(byte_vector: &[u8]) -> Vec<Fragment> {
+        Self::disassemble(&checksum::append(byte_vector))
+    }
+
+    /// Reassembles fragments produced by `disassemble_checked`, verifying and
+    /// stripping the checksum trailer.
+    ///
+    /// # Parameters
+    /// - `fragments`: Slice of fragments that are to be reassembled.
+    ///
+    /// # Returns
+    /// Returns byte vector of the data reassembled from fragments.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `try_reassemble` if the fragments are malformed, or
+    /// `AssemblerError::ChecksumMismatch` if the data was corrupted.
+    fn reassemble_checked(fragments: &[Fragment]) -> Result<Vec<u8>, AssemblerError> {
+        checksum::verify(Self::try_reassemble(fragments)?, None)
+    }
+
+    /// Fragmentizes a byte slice followed by a CRC-32 trailer into `MsgFragment`
+    /// packets, see `disassemble_to_packets`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the bytes cannot be fragmentized, see `try_disassemble`.
+    fn disassemble_to_packets_checked(
+        byte_vector: &[u8],
+        routing_header: &SourceRoutingHeader,
+        session_id: u64,
+    ) -> Vec<Packet> {
+        Self::disassemble_to_packets(&checksum::append(byte_vector), routing_header, session_id)
+    }
+
+    /// Reassembles the messages carried by packets produced by
+    /// `disassemble_to_packets_checked`, verifying the checksum of every session.
+    ///
+    /// # Returns
+    /// Returns the result of reassembling each session, keyed by source and
+    /// `session_id`. Checksum errors name the session that failed.
+    fn reassemble_packets_checked(
+        packets: &[Packet],
+    ) -> BTreeMap<(NodeId, u64), Result<Vec<u8>, AssemblerError>> {
+        Self::reassemble_packets(packets)
+            .into_iter()
+            .map(|((source, session_id), result)| {
+                let result = result.and_then(|bytes| checksum::verify(bytes, Some(session_id)));
+                ((source, session_id), result)
+            })
+            .collect()
+    }
 }
@@ -0,0 +1,101 @@
+use std::ops::Range;
+
+/// Fragment indexes of a message that have not been received yet, compressed
+/// into ranges, so that exactly the lost fragments can be requested again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingFragments {
+    total_n_fragments: u64,
+    ranges: Vec<Range<u64>>,
+}
+
+impl MissingFragments {
+    /// Builds the missing set from the received flag of every fragment index.
+    pub(crate) fn from_received(received: &[bool]) -> Self {
+        Self::from_indexes(
+            received.len() as u64,
+            received
+                .iter()
+                .zip(0..)
+                .filter(|(received, _)| !**received)
+                .map(|(_, index)| index),
+        )
+    }
+
+    /// Decodes a bitmap produced by `to_bitmap`.
+    ///
+    /// Bits beyond `total_n_fragments` are ignored, missing bytes mean that the
+    /// corresponding fragments were received.
+    #[must_use]
+    pub fn from_bitmap(bitmap: &[u8], total_n_fragments: u64) -> Self {
+        Self::from_indexes(
+            total_n_fragments,
+            bitmap
+                .iter()
+                .zip((0..).step_by(8))
+                .flat_map(|(byte, first)| {
+                    (0..8)
+                        .filter(move |bit| byte & (0x80 >> bit) != 0)
+                        .map(move |bit| first + bit)
+                })
+                .take_while(move |index| *index < total_n_fragments),
+        )
+    }
+
+    /// Builds the missing set from ascending fragment indexes.
+    fn from_indexes(total_n_fragments: u64, indexes: impl Iterator<Item = u64>) -> Self {
+        let mut ranges: Vec<Range<u64>> = Vec::new();
+        for index in indexes {
+            match ranges.last_mut() {
+                Some(range) if range.end == index => range.end += 1,
+                _ => ranges.push(index..index + 1),
+            }
+        }
+        MissingFragments {
+            total_n_fragments,
+            ranges,
+        }
+    }
+
+    #[must_use]
+    pub fn total_n_fragments(&self) -> u64 {
+        self.total_n_fragments
+    }
+
+    /// Returns the missing indexes as ascending, non-adjacent ranges.
+    #[must_use]
+    pub fn ranges(&self) -> &[Range<u64>] {
+        &self.ranges
+    }
+
+    /// Returns the missing indexes in ascending order.
+    pub fn indexes(&self) -> impl Iterator<Item = u64> + '_ {
+        self.ranges.iter().flat_map(Clone::clone)
+    }
+
+    /// Returns the number of missing fragments.
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.ranges
+            .iter()
+            .map(|range| range.end - range.start)
+            .sum()
+    }
+
+    /// Returns `true` if no fragment is missing.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Encodes the missing set as a bitmap of `total_n_fragments` bits, where bit
+    /// `i` is set if fragment `i` is missing. Bits are ordered from the most
+    /// significant bit of the first byte onwards.
+    #[must_use]
+    pub fn to_bitmap(&self) -> Vec<u8> {
+        let mut bitmap = vec![0; self.total_n_fragments.div_ceil(8) as usize];
+        for index in self.indexes() {
+            bitmap[(index / 8) as usize] |= 0x80 >> (index % 8);
+        }
+        bitmap
+    }
+}
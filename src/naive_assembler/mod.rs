@@ -1,85 +1,137 @@
-use std::collections::HashSet;
-
 use wg_2024::packet::{Fragment, FRAGMENT_DSIZE};
 
-use crate::Assembler;
+use crate::fragment_iter::disassemble_iter;
+use crate::{Assembler, AssemblerError};
 
+#[derive(Debug, Default, Clone, Copy)]
 pub struct NaiveAssembler {}
 
 impl Assembler for NaiveAssembler {
-    fn reassemble(fragments: &[Fragment]) -> Vec<u8> {
-        // Make sure that fragments are unique
-        let mut existing = HashSet::new();
-        for fragment in fragments {
-            assert!(
-                existing.insert(fragment.fragment_index),
-                "Fragments cannot be reassemble due to duplicate fragment(s)"
-            );
-        }
-
-        // Sort fragments according to indexes for cleaner reassembly
-        let mut fragments = fragments.to_vec();
-        fragments.sort_by_key(|f| f.fragment_index);
-        let fragments = fragments;
+    fn try_reassemble(fragments: &[Fragment]) -> Result<Vec<u8>, AssemblerError> {
+        let fragments = order_fragments(fragments)?;
 
         // Calculate the size needed for byte vector
-        let size_for_alloc = if fragments.len() == 1 {
-            /*
-            In the case there is only one fragment, then the size
-            shall be equal to the size of the fragment data.
-            Panic if no first element exists.
-             */
-            fragments.first().unwrap().length as usize
-        } else {
-            /*
-             In the case there are multiple fragments, then the size
-             shall be equal to amount of fragments - 1 multiplied by
-            the fragment data length constant plus the size of the
-             last fragment data.
-             */
-            let size_of_last_fragment = fragments.last().unwrap().length as usize;
-            let size_of_full_fragments = (fragments.len() - 1) * FRAGMENT_DSIZE;
-            size_of_last_fragment + size_of_full_fragments
-        };
+        let last_length = fragments.last().map_or(0, |f| f.length);
+        let size_for_alloc = message_size(fragments.len(), last_length);
 
         // Cheap initialization to avoid reallocation
         let mut byte_vector = vec![0; size_for_alloc];
 
-        // Populate byte vector with the fragments
-        for (index, fragment) in fragments.iter().enumerate() {
-            let offset = index * FRAGMENT_DSIZE;
-            if fragment.length == u8::try_from(FRAGMENT_DSIZE).unwrap() {
-                byte_vector.splice(offset.., fragment.data);
-            } else {
-                byte_vector.splice(
-                    offset..,
-                    fragment.data[..fragment.length as usize].iter().copied(),
-                );
-            }
+        // Populate byte vector with the fragments, each one directly at its offset
+        for fragment in fragments {
+            write_fragment(&mut byte_vector, fragment);
         }
-        byte_vector
+        Ok(byte_vector)
     }
 
-    /// # Panics
-    ///
-    /// Will panic if `fragments` is empty or contains duplicate fragment indexes.
-    fn disassemble(byte_vector: &[u8]) -> Vec<Fragment> {
-        byte_vector
-            .chunks(FRAGMENT_DSIZE)
-            .enumerate()
-            .map(|(index, chunk)| {
-                // not the most efficient way to deal with this but it works
-                let mut byte_array = [0; FRAGMENT_DSIZE];
-                byte_array[..chunk.len()].copy_from_slice(chunk);
+    fn try_disassemble(byte_vector: &[u8]) -> Result<Vec<Fragment>, AssemblerError> {
+        Ok(disassemble_iter(byte_vector).collect())
+    }
+}
 
-                Fragment {
-                    fragment_index: u64::try_from(index).unwrap(),
-                    total_n_fragments: u64::try_from(byte_vector.len().div_ceil(FRAGMENT_DSIZE))
-                        .unwrap(),
-                    length: u8::try_from(chunk.len()).unwrap(),
-                    data: byte_array,
-                }
-            })
-            .collect()
+/// Checks that `fragments` form exactly one complete message.
+///
+/// # Returns
+/// Returns the fragments ordered by their index.
+pub(crate) fn order_fragments(fragments: &[Fragment]) -> Result<Vec<&Fragment>, AssemblerError> {
+    let total_n_fragments = fragments
+        .first()
+        .ok_or(AssemblerError::Empty)?
+        .total_n_fragments;
+
+    // Make sure that fragments are well-formed
+    for fragment in fragments {
+        check_fragment(fragment, total_n_fragments)?;
+    }
+    if total_n_fragments > fragments.len() as u64 {
+        return Err(AssemblerError::MissingIndex(first_missing_index(fragments)));
+    }
+
+    // Look up every fragment by its index, making sure that fragments are unique
+    let mut slots: Vec<Option<&Fragment>> = vec![None; total_n_fragments as usize];
+    for fragment in fragments {
+        let slot = &mut slots[fragment.fragment_index as usize];
+        if slot.is_some() {
+            return Err(AssemblerError::DuplicateIndex(fragment.fragment_index));
+        }
+        *slot = Some(fragment);
+    }
+
+    // Make sure that no fragment is missing
+    slots
+        .into_iter()
+        .zip(0..)
+        .map(|(slot, index)| slot.ok_or(AssemblerError::MissingIndex(index)))
+        .collect()
+}
+
+/// Returns the lowest fragment index that does not appear in `fragments`.
+fn first_missing_index(fragments: &[Fragment]) -> u64 {
+    let mut indexes: Vec<u64> = fragments.iter().map(|f| f.fragment_index).collect();
+    indexes.sort_unstable();
+    indexes.dedup();
+    indexes
+        .iter()
+        .zip(0..)
+        .find(|(index, expected)| **index != *expected)
+        .map_or(indexes.len() as u64, |(_, expected)| expected)
+}
+
+/// Copies the data of `fragment` into `byte_vector` at the offset given by its index.
+///
+/// `byte_vector` must be large enough to hold the fragment.
+pub(crate) fn write_fragment(byte_vector: &mut [u8], fragment: &Fragment) {
+    let offset = fragment.fragment_index as usize * FRAGMENT_DSIZE;
+    let length = fragment.length as usize;
+    byte_vector[offset..offset + length].copy_from_slice(&fragment.data[..length]);
+}
+
+/// Calculates the size of a message made of `total_n_fragments` fragments
+/// whose last fragment carries `last_length` bytes.
+pub(crate) fn message_size(total_n_fragments: usize, last_length: u8) -> usize {
+    if total_n_fragments <= 1 {
+        /*
+        In the case there is only one fragment, then the size
+        shall be equal to the size of the fragment data.
+         */
+        last_length as usize
+    } else {
+        /*
+         In the case there are multiple fragments, then the size
+         shall be equal to amount of fragments - 1 multiplied by
+        the fragment data length constant plus the size of the
+         last fragment data.
+         */
+        let size_of_last_fragment = last_length as usize;
+        let size_of_full_fragments = (total_n_fragments - 1) * FRAGMENT_DSIZE;
+        size_of_last_fragment + size_of_full_fragments
+    }
+}
+
+/// Checks the fields of a single fragment against the `total_n_fragments`
+/// expected for its message.
+pub(crate) fn check_fragment(
+    fragment: &Fragment,
+    total_n_fragments: u64,
+) -> Result<(), AssemblerError> {
+    if fragment.total_n_fragments != total_n_fragments {
+        return Err(AssemblerError::InconsistentTotal {
+            fragment_index: fragment.fragment_index,
+            expected: total_n_fragments,
+            found: fragment.total_n_fragments,
+        });
+    }
+    if fragment.fragment_index >= total_n_fragments {
+        return Err(AssemblerError::IndexOutOfRange {
+            fragment_index: fragment.fragment_index,
+            total_n_fragments,
+        });
+    }
+    if fragment.length as usize > FRAGMENT_DSIZE {
+        return Err(AssemblerError::InvalidLength {
+            fragment_index: fragment.fragment_index,
+            length: fragment.length,
+        });
     }
+    Ok(())
 }
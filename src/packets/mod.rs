@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+
+use wg_2024::network::{NodeId, SourceRoutingHeader};
+use wg_2024::packet::{Ack, Fragment, Packet, PacketType};
+
+/// Wraps every fragment into a `MsgFragment` packet of the given session, see
+/// `fragment_packet`.
+pub(crate) fn fragments_to_packets(
+    fragments: Vec<Fragment>,
+    routing_header: &SourceRoutingHeader,
+    session_id: u64,
+) -> Vec<Packet> {
+    fragments
+        .into_iter()
+        .map(|fragment| fragment_packet(fragment, routing_header, session_id))
+        .collect()
+}
+
+/// Wraps a fragment into a `MsgFragment` packet of the given session.
+///
+/// The routing header is copied into the packet with its `hop_index` set to 1,
+/// as expected by the first drone on the route, so `routing_header.hops` must
+/// start with the sending node.
+pub(crate) fn fragment_packet(
+    fragment: Fragment,
+    routing_header: &SourceRoutingHeader,
+    session_id: u64,
+) -> Packet {
+    Packet {
+        routing_header: SourceRoutingHeader {
+            hop_index: 1,
+            hops: routing_header.hops.clone(),
+        },
+        session_id,
+        pack_type: PacketType::MsgFragment(fragment),
+    }
+}
+
+/// Collects the fragments carried by `MsgFragment` packets, grouped by source, i.e.
+/// the first hop of their route, and `session_id`. Packets of any other type and
+/// packets with an empty route are skipped.
+pub(crate) fn fragments_by_session(packets: &[Packet]) -> BTreeMap<(NodeId, u64), Vec<Fragment>> {
+    let mut sessions: BTreeMap<(NodeId, u64), Vec<Fragment>> = BTreeMap::new();
+    for packet in packets {
+        let PacketType::MsgFragment(fragment) = &packet.pack_type else {
+            continue;
+        };
+        let Some(&source) = packet.routing_header.hops.first() else {
+            continue;
+        };
+        sessions
+            .entry((source, packet.session_id))
+            .or_default()
+            .push(fragment.clone());
+    }
+    sessions
+}
+
+/// Builds the `Ack` of a fragment carried by `packet`, sent back along the route
+/// the packet travelled and with the same `session_id`.
+pub(crate) fn ack_packet(packet: &Packet, fragment_index: u64) -> Packet {
+    Packet {
+        routing_header: reversed_route(&packet.routing_header),
+        session_id: packet.session_id,
+        pack_type: PacketType::Ack(Ack { fragment_index }),
+    }
+}
+
+/// Reverses the hops travelled up to the node at `hop_index`, which receives the
+/// packet, so that it becomes the first hop of the returned route. The `hop_index`
+/// of the returned route is 1, as expected by the first drone.
+pub(crate) fn reversed_route(routing_header: &SourceRoutingHeader) -> SourceRoutingHeader {
+    let last = routing_header
+        .hop_index
+        .min(routing_header.hops.len().saturating_sub(1));
+    let mut hops: Vec<_> = routing_header.hops.iter().take(last + 1).copied().collect();
+    hops.reverse();
+    SourceRoutingHeader { hop_index: 1, hops }
+}
@@ -0,0 +1,129 @@
+use wg_2024::packet::Fragment;
+
+use crate::envelope::{self, EnvelopeError, MessageKind};
+use crate::naive_assembler::NaiveAssembler;
+use crate::stateful_assembler::StatefulAssembler;
+use crate::{checksum, AssemblerError};
+
+/// Reversible transformation of a payload, applied before fragmentation and
+/// undone after reassembly, e.g. compression, checksumming or framing.
+pub trait PayloadTransform {
+    /// Transforms a payload about to be fragmentized.
+    ///
+    /// # Parameters
+    /// - `payload`: Bytes produced by the previous stage.
+    ///
+    /// # Returns
+    /// Returns the bytes handed to the next stage.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AssemblerError` if the payload cannot be encoded.
+    fn encode(&mut self, payload: &[u8]) -> Result<Vec<u8>, AssemblerError>;
+
+    /// Undoes `encode` on a reassembled payload.
+    ///
+    /// # Parameters
+    /// - `payload`: Bytes produced by the next stage.
+    ///
+    /// # Returns
+    /// Returns the bytes originally passed to `encode`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AssemblerError` if the payload was not produced by `encode`
+    /// or was corrupted.
+    fn decode(&mut self, payload: &[u8]) -> Result<Vec<u8>, AssemblerError>;
+}
+
+/// `StatefulAssembler` applying a stack of `PayloadTransform`s in order before
+/// fragmentation, and in reverse order after reassembly.
+///
+/// The terminal stage is `NaiveAssembler` unless replaced with `with_assembler`.
+pub struct Pipeline {
+    transforms: Vec<Box<dyn PayloadTransform>>,
+    assembler: Box<dyn StatefulAssembler>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pipeline {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_assembler(Box::new(NaiveAssembler::default()))
+    }
+
+    #[must_use]
+    pub fn with_assembler(assembler: Box<dyn StatefulAssembler>) -> Self {
+        Pipeline {
+            transforms: Vec::new(),
+            assembler,
+        }
+    }
+
+    /// Appends a transform, applied after every transform added before it.
+    #[must_use]
+    pub fn then(mut self, transform: impl PayloadTransform + 'static) -> Self {
+        self.transforms.push(Box::new(transform));
+        self
+    }
+}
+
+impl StatefulAssembler for Pipeline {
+    fn fragmentize(&mut self, byte_vector: &[u8]) -> Result<Vec<Fragment>, AssemblerError> {
+        let mut payload = byte_vector.to_vec();
+        for transform in &mut self.transforms {
+            payload = transform.encode(&payload)?;
+        }
+        self.assembler.fragmentize(&payload)
+    }
+
+    fn assemble(&mut self, fragments: &[Fragment]) -> Result<Vec<u8>, AssemblerError> {
+        let mut payload = self.assembler.assemble(fragments)?;
+        for transform in self.transforms.iter_mut().rev() {
+            payload = transform.decode(&payload)?;
+        }
+        Ok(payload)
+    }
+}
+
+/// Appends a CRC-32 trailer, see `checksum::append`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Checksum;
+
+impl PayloadTransform for Checksum {
+    fn encode(&mut self, payload: &[u8]) -> Result<Vec<u8>, AssemblerError> {
+        Ok(checksum::append(payload))
+    }
+
+    fn decode(&mut self, payload: &[u8]) -> Result<Vec<u8>, AssemblerError> {
+        checksum::verify(payload.to_vec(), None)
+    }
+}
+
+/// Prepends an envelope header of the given kind, see `envelope::wrap`.
+/// Decoding fails on envelopes of any other kind.
+#[derive(Debug, Clone, Copy)]
+pub struct Envelope(pub MessageKind);
+
+impl PayloadTransform for Envelope {
+    fn encode(&mut self, payload: &[u8]) -> Result<Vec<u8>, AssemblerError> {
+        Ok(envelope::wrap(self.0, payload))
+    }
+
+    fn decode(&mut self, payload: &[u8]) -> Result<Vec<u8>, AssemblerError> {
+        let (kind, payload) = envelope::unwrap(payload)?;
+        if kind != self.0 {
+            return Err(EnvelopeError::UnexpectedKind {
+                expected: self.0,
+                found: kind,
+            }
+            .into());
+        }
+        Ok(payload.to_vec())
+    }
+}
@@ -0,0 +1,489 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+use wg_2024::network::NodeId;
+use wg_2024::packet::{Fragment, Packet, PacketType, FRAGMENT_DSIZE};
+
+use crate::clock::{Clock, SystemClock};
+use crate::config::{check_message_limits, AssemblerConfig, DuplicatePolicy, Limit};
+use crate::missing_fragments::MissingFragments;
+use crate::naive_assembler::{check_fragment, message_size, write_fragment};
+use crate::packets::ack_packet;
+use crate::validation::{check_duplicate, check_fragment_strict};
+use crate::{checksum, AssemblerError};
+
+/// Collects fragments arriving one at a time, out of order and interleaved
+/// across sessions, and hands back each message once it is complete.
+///
+/// Partial messages are keyed by the `NodeId` of their source and their `session_id`.
+/// Memory is preallocated from `total_n_fragments`, within the limits of an
+/// `AssemblerConfig`. Messages left incomplete for longer than the configured
+/// idle timeout, as measured by the clock `C`, can be evicted.
+#[derive(Debug, Default)]
+pub struct ReassemblyBuffer<C = SystemClock> {
+    config: AssemblerConfig,
+    clock: C,
+    sessions: HashMap<(NodeId, u64), PartialMessage>,
+    sessions_per_source: HashMap<NodeId, usize>,
+    buffered_bytes: usize,
+    /// Completed messages per source, oldest first.
+    completed: HashMap<NodeId, VecDeque<CompletedMessage>>,
+}
+
+/// Result of adding a packet with `ReassemblyBuffer::insert_packet`.
+#[derive(Debug)]
+pub struct PacketOutcome {
+    /// `Ack` packet to send back, if the packet carried a fragment that was accepted.
+    pub ack: Option<Packet>,
+    /// Result of adding the fragment, see `ReassemblyBuffer::insert`.
+    pub result: Result<Option<Vec<u8>>, AssemblerError>,
+}
+
+/// A message already handed back or failed, remembered to recognize late duplicates.
+#[derive(Debug)]
+struct CompletedMessage {
+    session_id: u64,
+    total_n_fragments: u64,
+    last_activity: Instant,
+    /// Error of a message that failed its checksum, returned again for its late
+    /// fragments so that they are not acknowledged.
+    failure: Option<AssemblerError>,
+}
+
+/// A message whose fragments have not all arrived yet.
+#[derive(Debug)]
+struct PartialMessage {
+    total_n_fragments: u64,
+    received: Vec<bool>,
+    lengths: Vec<u8>,
+    missing: usize,
+    byte_vector: Vec<u8>,
+    last_activity: Instant,
+}
+
+impl PartialMessage {
+    /// Creates a message of `total_n_fragments` fragments, which must have been
+    /// checked against the limits of the buffer.
+    fn new(total_n_fragments: u64, now: Instant) -> Self {
+        let total = total_n_fragments as usize;
+        PartialMessage {
+            total_n_fragments,
+            received: vec![false; total],
+            lengths: vec![0; total],
+            missing: total,
+            // Preallocate as if every fragment was full, trimmed on completion
+            byte_vector: vec![0; total * FRAGMENT_DSIZE],
+            last_activity: now,
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.missing == 0
+    }
+
+    fn insert(
+        &mut self,
+        fragment: &Fragment,
+        config: &AssemblerConfig,
+        now: Instant,
+    ) -> Result<(), AssemblerError> {
+        check_fragment(fragment, self.total_n_fragments)?;
+        if config.strict {
+            check_fragment_strict(fragment)?;
+        }
+        let index = fragment.fragment_index as usize;
+        if self.received[index] {
+            return check_duplicate(
+                self.lengths[index],
+                self.payload(index),
+                fragment,
+                config.duplicate_policy,
+            );
+        }
+        self.received[index] = true;
+        self.lengths[index] = fragment.length;
+        self.missing -= 1;
+        self.last_activity = now;
+
+        write_fragment(&mut self.byte_vector, fragment);
+        Ok(())
+    }
+
+    /// Returns `true` if a fragment with the same index, length and data as
+    /// `fragment` was already received.
+    fn holds(&self, fragment: &Fragment) -> bool {
+        let Ok(index) = usize::try_from(fragment.fragment_index) else {
+            return false;
+        };
+        fragment.total_n_fragments == self.total_n_fragments
+            && self.received.get(index) == Some(&true)
+            && check_duplicate(
+                self.lengths[index],
+                self.payload(index),
+                fragment,
+                DuplicatePolicy::VerifyIdentical,
+            )
+            .is_ok()
+    }
+
+    /// Returns the bytes written by the received fragment at `index`.
+    fn payload(&self, index: usize) -> &[u8] {
+        let offset = index * FRAGMENT_DSIZE;
+        &self.byte_vector[offset..offset + self.lengths[index] as usize]
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        let last_length = self.lengths.last().copied().unwrap_or(0);
+        let size = message_size(self.received.len(), last_length);
+        self.byte_vector.truncate(size);
+        self.byte_vector
+    }
+}
+
+impl ReassemblyBuffer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_config(config: AssemblerConfig) -> Self {
+        Self::with_clock(config, SystemClock)
+    }
+
+    /// Creates a buffer for messages sent with a checksum trailer, e.g. by
+    /// `Assembler::disassemble_to_packets_checked`. The trailer is verified and
+    /// stripped from every completed message.
+    #[must_use]
+    pub fn with_checksum() -> Self {
+        Self::with_config(AssemblerConfig {
+            verify_checksum: true,
+            ..AssemblerConfig::default()
+        })
+    }
+}
+
+impl<C: Clock> ReassemblyBuffer<C> {
+    /// Creates a buffer measuring idle timeouts with the given clock.
+    #[must_use]
+    pub fn with_clock(config: AssemblerConfig, clock: C) -> Self {
+        ReassemblyBuffer {
+            config,
+            clock,
+            sessions: HashMap::new(),
+            sessions_per_source: HashMap::new(),
+            buffered_bytes: 0,
+            completed: HashMap::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn config(&self) -> &AssemblerConfig {
+        &self.config
+    }
+
+    /// Adds a fragment of the message identified by `source` and `session_id`.
+    ///
+    /// # Returns
+    /// Returns the reassembled byte vector when `fragment` was the last missing
+    /// one, `None` while the message is still incomplete. A completed message is
+    /// returned exactly once: fragments of it arriving later, e.g. retransmitted
+    /// after a lost `Ack`, return `None` until the message stays idle for the
+    /// configured `idle_timeout`, see `evict_expired`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AssemblerError` if the fragment does not match the message it
+    /// belongs to, or if it was already received and the configured
+    /// `DuplicatePolicy` does not accept it. The fragment is then discarded, while
+    /// the rest of the message is kept.
+    ///
+    /// In strict mode, also returns the errors of `validation::validate_fragments`
+    /// for a fragment violating the protocol.
+    ///
+    /// Returns `AssemblerError::LimitExceeded` if the first fragment of a message
+    /// announces a message that cannot be buffered within the configured limits.
+    ///
+    /// When checksums are verified, returns `AssemblerError::ChecksumMismatch` if
+    /// the completed message was corrupted. The message is then discarded and
+    /// remembered as failed: its fragments arriving later return the same error
+    /// instead of starting a new message.
+    ///
+    /// Fragments of a message already returned cannot be compared anymore, so
+    /// they return `None` whatever the configured `DuplicatePolicy`, as long as
+    /// they match the number of fragments of the message.
+    pub fn insert(
+        &mut self,
+        source: NodeId,
+        session_id: u64,
+        fragment: &Fragment,
+    ) -> Result<Option<Vec<u8>>, AssemblerError> {
+        let Some(partial) = self.accept(source, session_id, fragment)? else {
+            return Ok(None);
+        };
+        let total_n_fragments = partial.total_n_fragments;
+        let result = self.finish(session_id, partial);
+        self.remember_completed(
+            source,
+            session_id,
+            total_n_fragments,
+            result.as_ref().err().cloned(),
+        );
+        result.map(Some)
+    }
+
+    /// Adds the fragment carried by a `MsgFragment` packet, whose source is the
+    /// first hop of its routing header, and builds the `Ack` to send back.
+    ///
+    /// Fragments already received, or belonging to a message already returned,
+    /// are acknowledged again without being buffered, as the sender retransmits
+    /// them when an `Ack` is lost. This holds whatever the `DuplicatePolicy`, which
+    /// only decides about duplicates conflicting with the fragment received first.
+    ///
+    /// Packets of any other type are ignored.
+    ///
+    /// # Returns
+    /// Returns the `Ack` packet, travelling back along the reversed route with the
+    /// same `session_id`, if the fragment was accepted, and the result of
+    /// `insert`. The last fragment of a message failing its checksum is not
+    /// acknowledged, and neither are its retransmissions, so that the sender
+    /// does not consider it delivered.
+    pub fn insert_packet(&mut self, packet: &Packet) -> PacketOutcome {
+        let PacketType::MsgFragment(fragment) = &packet.pack_type else {
+            return PacketOutcome {
+                ack: None,
+                result: Ok(None),
+            };
+        };
+        let Some(source) = packet.routing_header.hops.first().copied() else {
+            return PacketOutcome {
+                ack: None,
+                result: Err(AssemblerError::EmptyRoute),
+            };
+        };
+        let held = self
+            .sessions
+            .get(&(source, packet.session_id))
+            .is_some_and(|partial| partial.holds(fragment));
+        if held {
+            return PacketOutcome {
+                ack: Some(ack_packet(packet, fragment.fragment_index)),
+                result: Ok(None),
+            };
+        }
+        let result = self.insert(source, packet.session_id, fragment);
+        PacketOutcome {
+            ack: result
+                .is_ok()
+                .then(|| ack_packet(packet, fragment.fragment_index)),
+            result,
+        }
+    }
+
+    /// Adds a fragment to its partial message, see `insert`. Fragments of a
+    /// message already completed are checked against it and dropped, or rejected
+    /// with its error if it failed.
+    ///
+    /// # Returns
+    /// Returns the message, no longer held by the buffer, if it is now complete.
+    fn accept(
+        &mut self,
+        source: NodeId,
+        session_id: u64,
+        fragment: &Fragment,
+    ) -> Result<Option<PartialMessage>, AssemblerError> {
+        let key = (source, session_id);
+        let now = self.clock.now();
+        if let Some(completed) = self.completed_mut(source, session_id) {
+            check_fragment(fragment, completed.total_n_fragments)?;
+            completed.last_activity = now;
+            return match &completed.failure {
+                Some(error) => Err(error.clone()),
+                None => Ok(None),
+            };
+        }
+        let partial = match self.sessions.entry(key) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                // Do not start tracking a message from a malformed fragment
+                check_fragment(fragment, fragment.total_n_fragments)?;
+                if self.config.strict {
+                    check_fragment_strict(fragment)?;
+                }
+                let size = check_limits(
+                    &self.config,
+                    self.sessions_per_source.get(&source).copied().unwrap_or(0),
+                    self.buffered_bytes,
+                    source,
+                    fragment.total_n_fragments,
+                )?;
+                *self.sessions_per_source.entry(source).or_default() += 1;
+                self.buffered_bytes += size;
+                entry.insert(PartialMessage::new(fragment.total_n_fragments, now))
+            }
+        };
+        partial.insert(fragment, &self.config, now)?;
+
+        if !partial.is_complete() {
+            return Ok(None);
+        }
+        Ok(self.release(key))
+    }
+
+    /// Remembers a completed or failed message, forgetting the oldest one of
+    /// `source` once `max_completed_per_source` are remembered.
+    fn remember_completed(
+        &mut self,
+        source: NodeId,
+        session_id: u64,
+        total_n_fragments: u64,
+        failure: Option<AssemblerError>,
+    ) {
+        let limit = self.config.max_completed_per_source;
+        if limit == 0 {
+            return;
+        }
+        let completed = self.completed.entry(source).or_default();
+        while completed.len() >= limit {
+            completed.pop_front();
+        }
+        completed.push_back(CompletedMessage {
+            session_id,
+            total_n_fragments,
+            last_activity: self.clock.now(),
+            failure,
+        });
+    }
+
+    fn completed_mut(&mut self, source: NodeId, session_id: u64) -> Option<&mut CompletedMessage> {
+        self.completed
+            .get_mut(&source)?
+            .iter_mut()
+            .find(|completed| completed.session_id == session_id)
+    }
+
+    /// Turns a completed message into its bytes, verifying the checksum if configured.
+    fn finish(&self, session_id: u64, partial: PartialMessage) -> Result<Vec<u8>, AssemblerError> {
+        let byte_vector = partial.into_bytes();
+        if self.config.verify_checksum {
+            return checksum::verify(byte_vector, Some(session_id));
+        }
+        Ok(byte_vector)
+    }
+
+    /// Discards the partial message identified by `source` and `session_id`.
+    ///
+    /// # Returns
+    /// Returns `true` if there was such a message.
+    pub fn remove(&mut self, source: NodeId, session_id: u64) -> bool {
+        self.release((source, session_id)).is_some()
+    }
+
+    /// Discards every partial message that has not received a fragment for at
+    /// least the configured `idle_timeout`. Completed messages remembered to drop
+    /// late duplicates are forgotten after the same time.
+    ///
+    /// # Returns
+    /// Returns the source and session id of every discarded message.
+    pub fn evict_expired(&mut self) -> Vec<(NodeId, u64)> {
+        let now = self.clock.now();
+        let expired: Vec<(NodeId, u64)> = self
+            .sessions
+            .iter()
+            .filter(|(_, partial)| {
+                now.saturating_duration_since(partial.last_activity) >= self.config.idle_timeout
+            })
+            .map(|(key, _)| *key)
+            .collect();
+        for key in &expired {
+            self.release(*key);
+        }
+        for completed in self.completed.values_mut() {
+            completed.retain(|completed| {
+                now.saturating_duration_since(completed.last_activity) < self.config.idle_timeout
+            });
+        }
+        self.completed.retain(|_, completed| !completed.is_empty());
+        expired
+    }
+
+    /// Removes a partial message, giving back its share of the limits.
+    fn release(&mut self, key: (NodeId, u64)) -> Option<PartialMessage> {
+        let partial = self.sessions.remove(&key)?;
+        let (source, _) = key;
+        if let Entry::Occupied(mut entry) = self.sessions_per_source.entry(source) {
+            *entry.get_mut() -= 1;
+            if *entry.get() == 0 {
+                entry.remove();
+            }
+        }
+        self.buffered_bytes -= partial.byte_vector.len();
+        Some(partial)
+    }
+
+    /// Returns the fragment indexes of the given message that have not arrived yet.
+    ///
+    /// # Returns
+    /// Returns `None` if no fragment of the message is being held.
+    #[must_use]
+    pub fn missing(&self, source: NodeId, session_id: u64) -> Option<MissingFragments> {
+        self.sessions
+            .get(&(source, session_id))
+            .map(|partial| MissingFragments::from_received(&partial.received))
+    }
+
+    /// Returns `true` if some fragments of the given message are being held.
+    #[must_use]
+    pub fn contains(&self, source: NodeId, session_id: u64) -> bool {
+        self.sessions.contains_key(&(source, session_id))
+    }
+
+    /// Returns the number of incomplete messages.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Returns `true` if no incomplete messages are being held.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Returns the number of bytes preallocated for incomplete messages.
+    #[must_use]
+    pub fn buffered_bytes(&self) -> usize {
+        self.buffered_bytes
+    }
+}
+
+/// Checks that a new message of `total_n_fragments` fragments from `source` can
+/// be buffered without exceeding the limits of `config`.
+///
+/// # Returns
+/// Returns the number of bytes to preallocate for the message.
+fn check_limits(
+    config: &AssemblerConfig,
+    source_sessions: usize,
+    buffered_bytes: usize,
+    source: NodeId,
+    total_n_fragments: u64,
+) -> Result<usize, AssemblerError> {
+    let requested = check_message_limits(config, total_n_fragments)?;
+    if source_sessions >= config.max_sessions_per_source {
+        return Err(AssemblerError::LimitExceeded(Limit::SessionsPerSource {
+            source,
+            limit: config.max_sessions_per_source,
+        }));
+    }
+    // Fits into usize, as it is not larger than the maximum message size
+    let size = requested as usize;
+    if buffered_bytes.saturating_add(size) > config.max_buffered_bytes {
+        return Err(AssemblerError::LimitExceeded(Limit::BufferedBytes {
+            limit: config.max_buffered_bytes,
+            requested: (buffered_bytes as u64).saturating_add(requested),
+        }));
+    }
+    Ok(size)
+}
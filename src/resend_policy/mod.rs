@@ -0,0 +1,86 @@
+use wg_2024::network::NodeId;
+use wg_2024::packet::NackType;
+
+/// What the sender should do with a fragment named by a `Nack`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResendDecision {
+    /// Resend the same fragment on the same route, e.g. after a drop.
+    Resend,
+    /// Resend the fragment on a new route, as the current one is broken.
+    /// `faulty_node` is the node to avoid, if the `Nack` names one.
+    Reroute { faulty_node: Option<NodeId> },
+    /// Stop resending the fragment, the message cannot be delivered.
+    GiveUp,
+}
+
+/// Decides how the sender reacts to each `Nack`, see `SendBuffer::decide_nack`.
+pub trait ResendPolicy {
+    /// Decides what to do with a fragment named by a `Nack`.
+    ///
+    /// # Parameters
+    /// - `nack_type`: Reason reported by the `Nack`.
+    /// - `nacks`: Number of `Nack`s received for the fragment so far, including
+    ///   this one.
+    ///
+    /// # Returns
+    /// Returns the decision for the fragment.
+    fn decide(&mut self, nack_type: &NackType, nacks: u32) -> ResendDecision;
+}
+
+/// Resends dropped fragments on the same route and reroutes fragments whose
+/// route is broken, giving up once a fragment was nacked `max_nacks` times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DefaultResendPolicy {
+    pub max_nacks: u32,
+}
+
+impl Default for DefaultResendPolicy {
+    fn default() -> Self {
+        DefaultResendPolicy { max_nacks: 16 }
+    }
+}
+
+impl ResendPolicy for DefaultResendPolicy {
+    fn decide(&mut self, nack_type: &NackType, nacks: u32) -> ResendDecision {
+        if nacks >= self.max_nacks {
+            return ResendDecision::GiveUp;
+        }
+        match nack_type {
+            NackType::Dropped => ResendDecision::Resend,
+            NackType::ErrorInRouting(node) => ResendDecision::Reroute {
+                faulty_node: Some(*node),
+            },
+            NackType::DestinationIsDrone | NackType::UnexpectedRecipient(_) => {
+                ResendDecision::Reroute { faulty_node: None }
+            }
+        }
+    }
+}
+
+/// Number of `Nack`s received, per `NackType`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NackCounters {
+    pub dropped: u64,
+    pub error_in_routing: u64,
+    pub destination_is_drone: u64,
+    pub unexpected_recipient: u64,
+}
+
+impl NackCounters {
+    /// Counts a `Nack` of the given type.
+    pub fn record(&mut self, nack_type: &NackType) {
+        let counter = match nack_type {
+            NackType::Dropped => &mut self.dropped,
+            NackType::ErrorInRouting(_) => &mut self.error_in_routing,
+            NackType::DestinationIsDrone => &mut self.destination_is_drone,
+            NackType::UnexpectedRecipient(_) => &mut self.unexpected_recipient,
+        };
+        *counter += 1;
+    }
+
+    /// Returns the number of `Nack`s of every type.
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.dropped + self.error_in_routing + self.destination_is_drone + self.unexpected_recipient
+    }
+}
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use crate::config::SendConfig;
+
+/// Clock granularity added to the variance term, so that a perfectly stable
+/// round-trip time does not produce a timeout equal to it.
+const GRANULARITY: Duration = Duration::from_millis(1);
+
+/// Estimates the round-trip time from `Ack`s and derives the retransmission
+/// timeout from it, as in RFC 6298 (Jacobson/Karels).
+#[derive(Debug, Clone)]
+pub struct RttEstimator {
+    smoothed_rtt: Option<Duration>,
+    rtt_variance: Duration,
+    rto: Duration,
+    min_rto: Duration,
+    max_rto: Duration,
+}
+
+impl Default for RttEstimator {
+    fn default() -> Self {
+        Self::new(&SendConfig::default())
+    }
+}
+
+impl RttEstimator {
+    /// Creates an estimator without measurements, whose timeout is `config.initial_rto`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `config.min_rto` is greater than `config.max_rto`.
+    #[must_use]
+    pub fn new(config: &SendConfig) -> Self {
+        assert!(
+            config.min_rto <= config.max_rto,
+            "min_rto {:?} is greater than max_rto {:?}",
+            config.min_rto,
+            config.max_rto
+        );
+        RttEstimator {
+            smoothed_rtt: None,
+            rtt_variance: Duration::ZERO,
+            rto: config.initial_rto.clamp(config.min_rto, config.max_rto),
+            min_rto: config.min_rto,
+            max_rto: config.max_rto,
+        }
+    }
+
+    /// Updates the estimate with the round-trip time of a fragment that was sent
+    /// only once, as the `Ack` of a retransmitted fragment is ambiguous.
+    pub fn sample(&mut self, rtt: Duration) {
+        let (smoothed_rtt, rtt_variance) = match self.smoothed_rtt {
+            None => (rtt, rtt / 2),
+            Some(smoothed_rtt) => {
+                let deviation = smoothed_rtt.abs_diff(rtt);
+                (
+                    smoothed_rtt * 7 / 8 + rtt / 8,
+                    self.rtt_variance * 3 / 4 + deviation / 4,
+                )
+            }
+        };
+        self.smoothed_rtt = Some(smoothed_rtt);
+        self.rtt_variance = rtt_variance;
+        self.rto =
+            (smoothed_rtt + (rtt_variance * 4).max(GRANULARITY)).clamp(self.min_rto, self.max_rto);
+    }
+
+    /// Returns the smoothed round-trip time, once a sample was taken.
+    #[must_use]
+    pub fn smoothed_rtt(&self) -> Option<Duration> {
+        self.smoothed_rtt
+    }
+
+    #[must_use]
+    pub fn rtt_variance(&self) -> Duration {
+        self.rtt_variance
+    }
+
+    /// Returns the retransmission timeout of a fragment sent for the first time.
+    #[must_use]
+    pub fn rto(&self) -> Duration {
+        self.rto
+    }
+
+    /// Returns the retransmission timeout of a fragment already retransmitted
+    /// `retries` times, doubled for every retry up to the maximum.
+    #[must_use]
+    pub fn backoff(&self, retries: u32) -> Duration {
+        self.rto
+            .saturating_mul(2u32.saturating_pow(retries))
+            .min(self.max_rto)
+    }
+}
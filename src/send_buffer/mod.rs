@@ -0,0 +1,433 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+use wg_2024::packet::{Ack, Fragment, Nack};
+
+use crate::clock::{Clock, SystemClock};
+use crate::config::SendConfig;
+use crate::resend_policy::{DefaultResendPolicy, NackCounters, ResendDecision, ResendPolicy};
+use crate::rtt::RttEstimator;
+
+/// Fragments of a single outgoing message, kept until every one of them is acknowledged.
+#[derive(Debug, Clone)]
+pub struct OutgoingSession {
+    fragments: Vec<Fragment>,
+    states: Vec<FragmentState>,
+    unacknowledged: usize,
+}
+
+/// Delivery state of a single outgoing fragment.
+#[derive(Debug, Clone, Default)]
+struct FragmentState {
+    acknowledged: bool,
+    nacks: u32,
+    /// Last time the fragment was sent, `None` while it is queued.
+    sent_at: Option<Instant>,
+    /// Number of retransmissions on timeout.
+    retries: u32,
+    /// Whether the fragment was sent more than once, so that its `Ack` cannot
+    /// be matched to a single transmission to measure the round-trip time.
+    retransmitted: bool,
+    /// Whether the fragment is no longer retransmitted on timeout.
+    given_up: bool,
+}
+
+impl OutgoingSession {
+    /// Creates a session for the fragments of a message, e.g. the ones returned
+    /// by `Assembler::disassemble`. Fragments are looked up by their position,
+    /// which matches their `fragment_index`.
+    #[must_use]
+    pub fn new(fragments: Vec<Fragment>) -> Self {
+        OutgoingSession {
+            states: vec![FragmentState::default(); fragments.len()],
+            unacknowledged: fragments.len(),
+            fragments,
+        }
+    }
+
+    /// Returns the fragment with the given index.
+    #[must_use]
+    pub fn fragment(&self, fragment_index: u64) -> Option<&Fragment> {
+        self.fragments.get(usize::try_from(fragment_index).ok()?)
+    }
+
+    fn state(&self, fragment_index: u64) -> Option<&FragmentState> {
+        self.states.get(usize::try_from(fragment_index).ok()?)
+    }
+
+    fn state_mut(&mut self, fragment_index: u64) -> Option<&mut FragmentState> {
+        self.states.get_mut(usize::try_from(fragment_index).ok()?)
+    }
+
+    /// Marks the fragment with the given index as acknowledged.
+    ///
+    /// # Returns
+    /// Returns `true` if the fragment exists and was not acknowledged before.
+    pub fn acknowledge(&mut self, fragment_index: u64) -> bool {
+        let Some(state) = self.state_mut(fragment_index) else {
+            return false;
+        };
+        if state.acknowledged {
+            return false;
+        }
+        state.acknowledged = true;
+        self.unacknowledged -= 1;
+        true
+    }
+
+    /// Returns `true` if the fragment with the given index has been acknowledged.
+    #[must_use]
+    pub fn is_fragment_acknowledged(&self, fragment_index: u64) -> bool {
+        self.state(fragment_index)
+            .is_some_and(|state| state.acknowledged)
+    }
+
+    /// Returns the number of `Nack`s received for the fragment with the given index.
+    #[must_use]
+    pub fn nacks(&self, fragment_index: u64) -> u32 {
+        self.state(fragment_index).map_or(0, |state| state.nacks)
+    }
+
+    /// Returns the number of times the fragment with the given index was
+    /// retransmitted because its `Ack` did not arrive in time.
+    #[must_use]
+    pub fn retries(&self, fragment_index: u64) -> u32 {
+        self.state(fragment_index).map_or(0, |state| state.retries)
+    }
+
+    /// Counts a `Nack` for the fragment with the given index.
+    ///
+    /// # Returns
+    /// Returns the number of `Nack`s received for the fragment, or `None` if it
+    /// does not exist.
+    fn record_nack(&mut self, fragment_index: u64) -> Option<u32> {
+        let state = self.state_mut(fragment_index)?;
+        state.nacks += 1;
+        Some(state.nacks)
+    }
+
+    /// Records that the fragment with the given index was sent at `now`.
+    fn record_sent(&mut self, fragment_index: u64, now: Instant) -> bool {
+        let Some(state) = self.state_mut(fragment_index) else {
+            return false;
+        };
+        state.retransmitted |= state.sent_at.is_some();
+        state.sent_at = Some(now);
+        true
+    }
+
+    /// Returns `true` once every fragment has been acknowledged.
+    #[must_use]
+    pub fn is_acknowledged(&self) -> bool {
+        self.unacknowledged == 0
+    }
+
+    /// Returns the fragments that have not been acknowledged yet.
+    pub fn unacknowledged(&self) -> impl Iterator<Item = &Fragment> {
+        self.fragments
+            .iter()
+            .zip(&self.states)
+            .filter(|(_, state)| !state.acknowledged)
+            .map(|(fragment, _)| fragment)
+    }
+}
+
+/// Result of handling an `Ack` for an outgoing session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AckOutcome {
+    /// The fragment is acknowledged, others are still awaiting their `Ack`.
+    Pending,
+    /// Every fragment of the session is acknowledged; the session was released.
+    Completed,
+    /// The session or the fragment is unknown, was never sent or was already
+    /// acknowledged.
+    Ignored,
+}
+
+/// Action due because a fragment was neither acknowledged nor nacked in time,
+/// see `SendBuffer::poll_timeouts`.
+#[derive(Debug, Clone)]
+pub enum TimerEvent {
+    /// The fragment must be sent again.
+    Retransmit { session_id: u64, fragment: Fragment },
+    /// The fragment ran out of retries, the message cannot be delivered.
+    GiveUp {
+        session_id: u64,
+        fragment_index: u64,
+    },
+}
+
+/// Remembers the fragments of outgoing messages per `session_id`, so that
+/// single fragments can be resent when a `Nack` comes back or no `Ack` arrives
+/// in time.
+///
+/// How each `Nack` is handled is decided by the resend policy `P`. Send times
+/// and retransmission timeouts are measured by the clock `C`.
+#[derive(Debug, Default)]
+pub struct SendBuffer<P = DefaultResendPolicy, C = SystemClock> {
+    policy: P,
+    config: SendConfig,
+    clock: C,
+    rtt: RttEstimator,
+    sessions: HashMap<u64, OutgoingSession>,
+    nack_counters: NackCounters,
+}
+
+impl SendBuffer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// # Panics
+    ///
+    /// Will panic if `config.min_rto` is greater than `config.max_rto`.
+    #[must_use]
+    pub fn with_config(config: SendConfig) -> Self {
+        Self::with_clock(DefaultResendPolicy::default(), config, SystemClock)
+    }
+}
+
+impl<P: ResendPolicy> SendBuffer<P> {
+    /// Creates a buffer deciding how to handle `Nack`s with the given policy.
+    #[must_use]
+    pub fn with_policy(policy: P) -> Self {
+        Self::with_clock(policy, SendConfig::default(), SystemClock)
+    }
+}
+
+impl<P: ResendPolicy, C: Clock> SendBuffer<P, C> {
+    /// Creates a buffer measuring send times and timeouts with the given clock.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `config.min_rto` is greater than `config.max_rto`.
+    #[must_use]
+    pub fn with_clock(policy: P, config: SendConfig, clock: C) -> Self {
+        SendBuffer {
+            policy,
+            rtt: RttEstimator::new(&config),
+            config,
+            clock,
+            sessions: HashMap::new(),
+            nack_counters: NackCounters::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn config(&self) -> &SendConfig {
+        &self.config
+    }
+
+    /// Stores the fragments of a message sent with `session_id`, replacing any
+    /// session previously stored with the same id. Every fragment is considered
+    /// sent now.
+    pub fn insert(&mut self, session_id: u64, fragments: Vec<Fragment>) {
+        let mut session = OutgoingSession::new(fragments);
+        let now = self.clock.now();
+        for state in &mut session.states {
+            state.sent_at = Some(now);
+        }
+        self.sessions.insert(session_id, session);
+    }
+
+    /// Stores the fragments of a message to be sent with `session_id`, replacing
+    /// any session previously stored with the same id. No fragment is
+    /// considered sent until it is passed to `mark_sent`.
+    pub fn insert_queued(&mut self, session_id: u64, fragments: Vec<Fragment>) {
+        self.sessions
+            .insert(session_id, OutgoingSession::new(fragments));
+    }
+
+    /// Records that a fragment was sent now, starting its retransmission timer.
+    ///
+    /// # Returns
+    /// Returns `false` if the session or the fragment is unknown.
+    pub fn mark_sent(&mut self, session_id: u64, fragment_index: u64) -> bool {
+        let now = self.clock.now();
+        self.sessions
+            .get_mut(&session_id)
+            .is_some_and(|session| session.record_sent(fragment_index, now))
+    }
+
+    /// Marks the fragment named by `ack` as acknowledged, measuring its
+    /// round-trip time if it was sent only once.
+    ///
+    /// # Returns
+    /// Returns `AckOutcome::Completed` when the whole session is acknowledged, in
+    /// which case it is removed from the buffer.
+    pub fn handle_ack(&mut self, session_id: u64, ack: &Ack) -> AckOutcome {
+        let Some(session) = self.sessions.get_mut(&session_id) else {
+            return AckOutcome::Ignored;
+        };
+        let Some(state) = session.state(ack.fragment_index) else {
+            return AckOutcome::Ignored;
+        };
+        // A queued fragment cannot have reached the destination yet
+        if state.sent_at.is_none() {
+            return AckOutcome::Ignored;
+        }
+        let sent_at = state.sent_at.filter(|_| !state.retransmitted);
+        if !session.acknowledge(ack.fragment_index) {
+            return AckOutcome::Ignored;
+        }
+        if let Some(sent_at) = sent_at {
+            self.rtt
+                .sample(self.clock.now().saturating_duration_since(sent_at));
+        }
+        if session.is_acknowledged() {
+            self.sessions.remove(&session_id);
+            AckOutcome::Completed
+        } else {
+            AckOutcome::Pending
+        }
+    }
+
+    /// Looks up the fragment named by `nack`.
+    ///
+    /// # Returns
+    /// Returns the exact fragment to resend, or `None` if the session or the
+    /// fragment is unknown or was already acknowledged.
+    #[must_use]
+    pub fn handle_nack(&self, session_id: u64, nack: &Nack) -> Option<&Fragment> {
+        let session = self.sessions.get(&session_id)?;
+        if session.is_fragment_acknowledged(nack.fragment_index) {
+            return None;
+        }
+        session.fragment(nack.fragment_index)
+    }
+
+    /// Counts the `Nack` by type and asks the resend policy what to do with the
+    /// fragment it names.
+    ///
+    /// Unless the decision is `ResendDecision::GiveUp`, the fragment is expected
+    /// to be resent right away and its retransmission timer restarts. Otherwise
+    /// it is no longer retransmitted on timeout.
+    ///
+    /// # Returns
+    /// Returns the decision and the fragment to resend, or `None` if the session
+    /// or the fragment is unknown, was never sent or was already acknowledged. After
+    /// `ResendDecision::GiveUp` the session is kept until it is `remove`d.
+    pub fn decide_nack(
+        &mut self,
+        session_id: u64,
+        nack: &Nack,
+    ) -> Option<(ResendDecision, &Fragment)> {
+        self.nack_counters.record(&nack.nack_type);
+        let now = self.clock.now();
+        let session = self.sessions.get_mut(&session_id)?;
+        if session.is_fragment_acknowledged(nack.fragment_index) {
+            return None;
+        }
+        // A queued fragment cannot have been dropped on its way yet
+        session.state(nack.fragment_index)?.sent_at?;
+        let nacks = session.record_nack(nack.fragment_index)?;
+        let decision = self.policy.decide(&nack.nack_type, nacks);
+        if decision == ResendDecision::GiveUp {
+            session.state_mut(nack.fragment_index)?.given_up = true;
+        } else {
+            session.record_sent(nack.fragment_index, now);
+        }
+        Some((decision, session.fragment(nack.fragment_index)?))
+    }
+
+    /// Collects the fragments whose retransmission timeout expired.
+    ///
+    /// The timeout of a fragment is the current estimate of `RttEstimator::rto`,
+    /// doubled for every retransmission already made. Expired fragments are
+    /// considered resent now, until they were retransmitted `max_retries` times,
+    /// after which they are given up and no longer retransmitted.
+    ///
+    /// # Returns
+    /// Returns the due events, ordered by `session_id` and `fragment_index`.
+    pub fn poll_timeouts(&mut self) -> Vec<TimerEvent> {
+        let now = self.clock.now();
+        let mut events = Vec::new();
+        for (session_id, session) in &mut self.sessions {
+            for (fragment, state) in session.fragments.iter().zip(&mut session.states) {
+                let Some(sent_at) = state.sent_at else {
+                    continue;
+                };
+                if state.acknowledged
+                    || state.given_up
+                    || now < sent_at + self.rtt.backoff(state.retries)
+                {
+                    continue;
+                }
+                if state.retries >= self.config.max_retries {
+                    state.given_up = true;
+                    events.push(TimerEvent::GiveUp {
+                        session_id: *session_id,
+                        fragment_index: fragment.fragment_index,
+                    });
+                } else {
+                    state.retries += 1;
+                    state.retransmitted = true;
+                    state.sent_at = Some(now);
+                    events.push(TimerEvent::Retransmit {
+                        session_id: *session_id,
+                        fragment: fragment.clone(),
+                    });
+                }
+            }
+        }
+        events.sort_by_key(|event| match event {
+            TimerEvent::Retransmit {
+                session_id,
+                fragment,
+            } => (*session_id, fragment.fragment_index),
+            TimerEvent::GiveUp {
+                session_id,
+                fragment_index,
+            } => (*session_id, *fragment_index),
+        });
+        events
+    }
+
+    /// Returns the earliest time at which `poll_timeouts` has something to do,
+    /// or `None` if no fragment is awaiting its `Ack`.
+    #[must_use]
+    pub fn next_timeout(&self) -> Option<Instant> {
+        self.sessions
+            .values()
+            .flat_map(|session| &session.states)
+            .filter(|state| !state.acknowledged && !state.given_up)
+            .filter_map(|state| Some(state.sent_at? + self.rtt.backoff(state.retries)))
+            .min()
+    }
+
+    /// Returns the round-trip time estimate driving the retransmission timeouts.
+    #[must_use]
+    pub fn rtt(&self) -> &RttEstimator {
+        &self.rtt
+    }
+
+    /// Returns the number of `Nack`s received per type.
+    #[must_use]
+    pub fn nack_counters(&self) -> &NackCounters {
+        &self.nack_counters
+    }
+
+    /// Returns the outgoing session stored with `session_id`.
+    #[must_use]
+    pub fn session(&self, session_id: u64) -> Option<&OutgoingSession> {
+        self.sessions.get(&session_id)
+    }
+
+    /// Releases the outgoing session stored with `session_id`.
+    pub fn remove(&mut self, session_id: u64) -> Option<OutgoingSession> {
+        self.sessions.remove(&session_id)
+    }
+
+    /// Returns the number of sessions awaiting acknowledgement.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Returns `true` if no session is awaiting acknowledgement.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+}
@@ -0,0 +1,145 @@
+use wg_2024::packet::{Fragment, FRAGMENT_DSIZE};
+
+use crate::config::{check_message_limits, AssemblerConfig, DuplicatePolicy};
+use crate::naive_assembler::NaiveAssembler;
+use crate::validation::{deduplicate, validate_fragments};
+use crate::{checksum, Assembler, AssemblerError};
+
+/// Object-safe companion of `Assembler` whose methods take `&mut self`, so that
+/// implementations can carry configuration and state, and be swapped at runtime
+/// as a `Box<dyn StatefulAssembler>`.
+///
+/// Every `Assembler` is a `StatefulAssembler` without state.
+pub trait StatefulAssembler {
+    /// Fragmentizes a byte slice into a vector of fragments.
+    ///
+    /// # Parameters
+    /// - `byte_vector`: Bytes to be fragmentized.
+    ///
+    /// # Returns
+    /// Returns a fragment vector.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AssemblerError` if `byte_vector` cannot be fragmentized.
+    fn fragmentize(&mut self, byte_vector: &[u8]) -> Result<Vec<Fragment>, AssemblerError>;
+
+    /// Reassembles data from fragments (`&[Fragment]`) into a single byte vector.
+    ///
+    /// # Parameters
+    /// - `fragments`: Slice of fragments that are to be reassembled, in any order.
+    ///
+    /// # Returns
+    /// Returns byte vector of the data reassembled from fragments.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AssemblerError` if the fragments cannot be reassembled.
+    fn assemble(&mut self, fragments: &[Fragment]) -> Result<Vec<u8>, AssemblerError>;
+}
+
+impl<A: Assembler> StatefulAssembler for A {
+    fn fragmentize(&mut self, byte_vector: &[u8]) -> Result<Vec<Fragment>, AssemblerError> {
+        A::try_disassemble(byte_vector)
+    }
+
+    fn assemble(&mut self, fragments: &[Fragment]) -> Result<Vec<u8>, AssemblerError> {
+        A::try_reassemble(fragments)
+    }
+}
+
+/// Counters of the messages handled by a `ConfiguredAssembler`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AssemblerStats {
+    pub messages_disassembled: u64,
+    pub messages_reassembled: u64,
+    /// Number of calls that returned an error.
+    pub errors: u64,
+}
+
+/// `StatefulAssembler` applying an `AssemblerConfig` on top of `NaiveAssembler`.
+///
+/// Messages exceeding `max_message_size` or `max_fragments_per_message` are
+/// refused in both directions, with the rule shared by `ReassemblyBuffer`.
+/// Duplicates are treated according to `duplicate_policy` and `strict` rejects
+/// every protocol violation. With `verify_checksum`, a CRC-32
+/// trailer is appended to outgoing messages and verified on incoming ones.
+#[derive(Debug, Clone, Default)]
+pub struct ConfiguredAssembler {
+    config: AssemblerConfig,
+    stats: AssemblerStats,
+}
+
+impl ConfiguredAssembler {
+    #[must_use]
+    pub fn new(config: AssemblerConfig) -> Self {
+        ConfiguredAssembler {
+            config,
+            stats: AssemblerStats::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn config(&self) -> &AssemblerConfig {
+        &self.config
+    }
+
+    #[must_use]
+    pub fn stats(&self) -> AssemblerStats {
+        self.stats
+    }
+
+    fn try_fragmentize(&self, byte_vector: &[u8]) -> Result<Vec<Fragment>, AssemblerError> {
+        let byte_vector = if self.config.verify_checksum {
+            checksum::append(byte_vector)
+        } else {
+            byte_vector.to_vec()
+        };
+        let total_n_fragments = byte_vector.len().div_ceil(FRAGMENT_DSIZE).max(1);
+        check_message_limits(&self.config, total_n_fragments as u64)?;
+        NaiveAssembler::try_disassemble(&byte_vector)
+    }
+
+    fn try_assemble(&self, fragments: &[Fragment]) -> Result<Vec<u8>, AssemblerError> {
+        let first = fragments.first().ok_or(AssemblerError::Empty)?;
+        check_message_limits(&self.config, first.total_n_fragments)?;
+
+        let unique;
+        let fragments = match self.config.duplicate_policy {
+            DuplicatePolicy::Reject => fragments,
+            policy => {
+                unique = deduplicate(fragments, policy)?;
+                &unique
+            }
+        };
+        if self.config.strict {
+            validate_fragments(fragments)?;
+        }
+        let byte_vector = NaiveAssembler::try_reassemble(fragments)?;
+        if self.config.verify_checksum {
+            checksum::verify(byte_vector, None)
+        } else {
+            Ok(byte_vector)
+        }
+    }
+}
+
+impl StatefulAssembler for ConfiguredAssembler {
+    fn fragmentize(&mut self, byte_vector: &[u8]) -> Result<Vec<Fragment>, AssemblerError> {
+        let result = self.try_fragmentize(byte_vector);
+        match result {
+            Ok(_) => self.stats.messages_disassembled += 1,
+            Err(_) => self.stats.errors += 1,
+        }
+        result
+    }
+
+    fn assemble(&mut self, fragments: &[Fragment]) -> Result<Vec<u8>, AssemblerError> {
+        let result = self.try_assemble(fragments);
+        match result {
+            Ok(_) => self.stats.messages_reassembled += 1,
+            Err(_) => self.stats.errors += 1,
+        }
+        result
+    }
+}
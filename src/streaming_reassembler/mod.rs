@@ -0,0 +1,198 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use wg_2024::packet::{Fragment, FRAGMENT_DSIZE};
+
+use crate::config::{check_fragments_per_message, AssemblerConfig, DuplicatePolicy, Limit};
+use crate::missing_fragments::MissingFragments;
+use crate::naive_assembler::check_fragment;
+use crate::validation::{check_duplicate, check_fragment_strict};
+use crate::AssemblerError;
+
+/// Reassembles a single message straight into a `Write` sink.
+///
+/// Fragments may arrive in any order. As soon as the contiguous prefix of the
+/// message grows, it is written to the sink, so only fragments arriving ahead
+/// of a gap are held in memory. The bytes written are the same as those
+/// returned by `Assembler::reassemble`.
+///
+/// The `AssemblerConfig` bounds `total_n_fragments` and the bytes held for
+/// out-of-order fragments. Its `max_message_size` does not apply, as the
+/// message is never held in memory as a whole.
+#[derive(Debug)]
+pub struct StreamingReassembler<W: Write> {
+    config: AssemblerConfig,
+    sink: W,
+    total_n_fragments: Option<u64>,
+    written_fragments: u64,
+    bytes_written: u64,
+    pending: BTreeMap<u64, Fragment>,
+}
+
+impl<W: Write> StreamingReassembler<W> {
+    #[must_use]
+    pub fn new(sink: W) -> Self {
+        Self::with_config(sink, AssemblerConfig::default())
+    }
+
+    #[must_use]
+    pub fn with_config(sink: W, config: AssemblerConfig) -> Self {
+        StreamingReassembler {
+            config,
+            sink,
+            total_n_fragments: None,
+            written_fragments: 0,
+            bytes_written: 0,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Accepts a fragment of the message and writes every fragment that
+    /// became contiguous with the bytes written so far.
+    ///
+    /// # Returns
+    /// Returns `true` once all `total_n_fragments` have been written and the
+    /// sink has been flushed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AssemblerError` if the fragment does not belong to the
+    /// message, exceeds a limit of the configuration or is a duplicate refused
+    /// by its `DuplicatePolicy`. The fragment is then discarded and the
+    /// reassembler can keep going.
+    ///
+    /// Returns `AssemblerError::Io` if the sink fails, after which it is
+    /// unknown which bytes reached it and the reassembler should be dropped.
+    ///
+    /// Duplicates of fragments already written cannot be compared anymore, so
+    /// `DuplicatePolicy::VerifyIdentical` accepts them like
+    /// `DuplicatePolicy::Ignore`.
+    pub fn insert(&mut self, fragment: &Fragment) -> Result<bool, AssemblerError> {
+        let total_n_fragments = self.total_n_fragments.unwrap_or(fragment.total_n_fragments);
+        check_fragment(fragment, total_n_fragments)?;
+        if self.config.strict {
+            check_fragment_strict(fragment)?;
+        }
+        check_fragments_per_message(&self.config, total_n_fragments)?;
+        self.total_n_fragments = Some(total_n_fragments);
+
+        let index = fragment.fragment_index;
+        if index < self.written_fragments {
+            return match self.config.duplicate_policy {
+                DuplicatePolicy::Reject => Err(AssemblerError::DuplicateIndex(index)),
+                _ => Ok(self.is_complete()),
+            };
+        }
+        if index > self.written_fragments {
+            let buffered_fragments = self.pending.len() as u64;
+            match self.pending.entry(index) {
+                Entry::Occupied(entry) => {
+                    let stored = entry.get();
+                    check_duplicate(
+                        stored.length,
+                        &stored.data[..stored.length as usize],
+                        fragment,
+                        self.config.duplicate_policy,
+                    )?;
+                }
+                Entry::Vacant(entry) => {
+                    let requested = (buffered_fragments + 1) * FRAGMENT_DSIZE as u64;
+                    if requested > self.config.max_buffered_bytes as u64 {
+                        return Err(AssemblerError::LimitExceeded(Limit::BufferedBytes {
+                            limit: self.config.max_buffered_bytes,
+                            requested,
+                        }));
+                    }
+                    entry.insert(fragment.clone());
+                }
+            }
+            return Ok(false);
+        }
+
+        self.write(fragment)?;
+        while let Some(next) = self.pending.remove(&self.written_fragments) {
+            self.write(&next)?;
+        }
+        if self.is_complete() {
+            self.sink
+                .flush()
+                .map_err(|error| AssemblerError::Io(error.kind()))?;
+        }
+        Ok(self.is_complete())
+    }
+
+    /// Writes the next fragment of the contiguous prefix to the sink.
+    fn write(&mut self, fragment: &Fragment) -> Result<(), AssemblerError> {
+        let is_last = Some(fragment.fragment_index + 1) == self.total_n_fragments;
+        let length = fragment.length as usize;
+        // Every fragment but the last one takes up a full slot, short ones are
+        // padded with zeros as done by `Assembler::reassemble`
+        let mut chunk = [0; FRAGMENT_DSIZE];
+        chunk[..length].copy_from_slice(&fragment.data[..length]);
+        let chunk = if is_last {
+            &chunk[..length]
+        } else {
+            &chunk[..]
+        };
+
+        self.sink
+            .write_all(chunk)
+            .map_err(|error| AssemblerError::Io(error.kind()))?;
+        self.written_fragments += 1;
+        self.bytes_written += chunk.len() as u64;
+        Ok(())
+    }
+
+    /// Returns `true` once every fragment of the message has been written.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.total_n_fragments == Some(self.written_fragments)
+    }
+
+    /// Returns the `total_n_fragments` of the message, once a fragment has
+    /// been accepted.
+    #[must_use]
+    pub fn total_n_fragments(&self) -> Option<u64> {
+        self.total_n_fragments
+    }
+
+    /// Returns the number of fragments written to the sink.
+    #[must_use]
+    pub fn written_fragments(&self) -> u64 {
+        self.written_fragments
+    }
+
+    /// Returns the number of bytes written to the sink.
+    #[must_use]
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Returns the number of fragments held back until the gap before them is filled.
+    #[must_use]
+    pub fn buffered_fragments(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns the fragments not received yet, once a fragment has been accepted.
+    #[must_use]
+    pub fn missing(&self) -> Option<MissingFragments> {
+        let total_n_fragments = self.total_n_fragments?;
+        let received: Vec<bool> = (0..total_n_fragments)
+            .map(|index| index < self.written_fragments || self.pending.contains_key(&index))
+            .collect();
+        Some(MissingFragments::from_received(&received))
+    }
+
+    #[must_use]
+    pub fn get_ref(&self) -> &W {
+        &self.sink
+    }
+
+    /// Returns the sink, dropping any fragments that were not written yet.
+    #[must_use]
+    pub fn into_inner(self) -> W {
+        self.sink
+    }
+}
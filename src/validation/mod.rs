@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use wg_2024::packet::{Fragment, FRAGMENT_DSIZE};
+
+use crate::config::DuplicatePolicy;
+use crate::naive_assembler::order_fragments;
+use crate::AssemblerError;
+
+/// Checks that `fragments` form exactly one complete message and that every
+/// fragment follows the protocol to the letter.
+///
+/// On top of the checks of `Assembler::try_reassemble`, every fragment but the
+/// last must be full, the last one must carry data unless it is the only one,
+/// and the padding after `length` must be zero.
+///
+/// # Errors
+///
+/// Returns the first violation found as an `AssemblerError` naming the
+/// offending `fragment_index`.
+pub fn validate_fragments(fragments: &[Fragment]) -> Result<(), AssemblerError> {
+    for fragment in order_fragments(fragments)? {
+        check_fragment_strict(fragment)?;
+    }
+    Ok(())
+}
+
+/// Checks the `length` and padding of a fragment, whose index and length must
+/// already be known to be within range.
+pub(crate) fn check_fragment_strict(fragment: &Fragment) -> Result<(), AssemblerError> {
+    let is_last = fragment.fragment_index + 1 == fragment.total_n_fragments;
+    let length = fragment.length as usize;
+    if !is_last && length != FRAGMENT_DSIZE {
+        return Err(AssemblerError::ShortFragment {
+            fragment_index: fragment.fragment_index,
+            length: fragment.length,
+        });
+    }
+    if is_last && length == 0 && fragment.total_n_fragments > 1 {
+        return Err(AssemblerError::ShortFragment {
+            fragment_index: fragment.fragment_index,
+            length: fragment.length,
+        });
+    }
+    if fragment.data[length..].iter().any(|byte| *byte != 0) {
+        return Err(AssemblerError::NonZeroPadding {
+            fragment_index: fragment.fragment_index,
+        });
+    }
+    Ok(())
+}
+
+/// Removes fragments whose `fragment_index` appeared earlier in `fragments`,
+/// according to `policy`.
+///
+/// # Returns
+/// Returns the first fragment of every index, in their original order.
+///
+/// # Errors
+///
+/// Returns `AssemblerError::DuplicateIndex` for any duplicate when `policy` is
+/// `DuplicatePolicy::Reject`, and `AssemblerError::ConflictingDuplicate` for a
+/// duplicate that differs from the first fragment when `policy` is
+/// `DuplicatePolicy::VerifyIdentical`.
+pub fn deduplicate(
+    fragments: &[Fragment],
+    policy: DuplicatePolicy,
+) -> Result<Vec<Fragment>, AssemblerError> {
+    let mut first_seen: HashMap<u64, &Fragment> = HashMap::new();
+    let mut unique = Vec::with_capacity(fragments.len());
+    for fragment in fragments {
+        let Some(first) = first_seen.get(&fragment.fragment_index) else {
+            first_seen.insert(fragment.fragment_index, fragment);
+            unique.push(fragment.clone());
+            continue;
+        };
+        check_duplicate(first.length, payload(first), fragment, policy)?;
+    }
+    Ok(unique)
+}
+
+/// Applies `policy` to `duplicate`, a fragment whose index was already received
+/// with the given `length` and payload bytes.
+pub(crate) fn check_duplicate(
+    length: u8,
+    payload_bytes: &[u8],
+    duplicate: &Fragment,
+    policy: DuplicatePolicy,
+) -> Result<(), AssemblerError> {
+    match policy {
+        DuplicatePolicy::Reject => Err(AssemblerError::DuplicateIndex(duplicate.fragment_index)),
+        DuplicatePolicy::Ignore => Ok(()),
+        DuplicatePolicy::VerifyIdentical => {
+            if duplicate.length == length && payload(duplicate) == payload_bytes {
+                Ok(())
+            } else {
+                Err(AssemblerError::ConflictingDuplicate {
+                    fragment_index: duplicate.fragment_index,
+                })
+            }
+        }
+    }
+}
+
+/// Returns the bytes of `fragment.data` covered by its `length`, or all of them
+/// if `length` is out of range.
+fn payload(fragment: &Fragment) -> &[u8] {
+    fragment
+        .data
+        .get(..fragment.length as usize)
+        .unwrap_or(&fragment.data)
+}
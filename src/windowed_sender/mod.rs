@@ -0,0 +1,326 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Instant;
+
+use wg_2024::network::SourceRoutingHeader;
+use wg_2024::packet::{Ack, Fragment, Nack, NackType, Packet};
+
+use crate::clock::{Clock, SystemClock};
+use crate::config::WindowConfig;
+use crate::packets;
+use crate::resend_policy::{DefaultResendPolicy, ResendDecision, ResendPolicy};
+use crate::send_buffer::{AckOutcome, SendBuffer, TimerEvent};
+
+/// Sending progress of a single outgoing message.
+#[derive(Debug, Clone)]
+struct WindowedSession {
+    routing_header: SourceRoutingHeader,
+    total_n_fragments: u64,
+    /// Index of the first fragment never sent.
+    next_fragment: u64,
+    in_flight: usize,
+    window: usize,
+    /// `Ack`s received since the window last grew.
+    acks_in_window: usize,
+    given_up: bool,
+}
+
+impl WindowedSession {
+    /// Grows the window by one fragment once a whole window was acknowledged.
+    fn increase(&mut self, config: &WindowConfig) {
+        self.acks_in_window += 1;
+        if self.acks_in_window >= self.window {
+            self.window = (self.window + 1).min(config.max_window.max(1));
+            self.acks_in_window = 0;
+        }
+    }
+
+    /// Halves the window after a drop.
+    fn decrease(&mut self, config: &WindowConfig) {
+        self.window = (self.window / 2).max(config.min_window).max(1);
+        self.acks_in_window = 0;
+    }
+}
+
+/// Sends the fragments of outgoing messages a window at a time, so that large
+/// messages do not flood the drones' channels.
+///
+/// Each session keeps at most its window of fragments in flight, i.e. sent and
+/// neither acknowledged nor given up. The window grows by one fragment per
+/// window of `Ack`s and is halved on every `NackType::Dropped` `Nack` (AIMD).
+/// Fragments are kept in a `SendBuffer`, which decides how `Nack`s are handled
+/// and retransmits fragments on timeout.
+#[derive(Debug, Default)]
+pub struct WindowedSender<P = DefaultResendPolicy, C = SystemClock> {
+    config: WindowConfig,
+    buffer: SendBuffer<P, C>,
+    sessions: BTreeMap<u64, WindowedSession>,
+    /// Fragments named by `Nack`s, to be resent by the next poll.
+    resend: VecDeque<(u64, u64)>,
+}
+
+impl WindowedSender {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_config(config: WindowConfig) -> Self {
+        Self::with_send_buffer(config, SendBuffer::new())
+    }
+}
+
+impl<P: ResendPolicy, C: Clock> WindowedSender<P, C> {
+    /// Creates a sender keeping its fragments in the given buffer, which sets the
+    /// resend policy, the retransmission timers and the clock.
+    #[must_use]
+    pub fn with_send_buffer(config: WindowConfig, buffer: SendBuffer<P, C>) -> Self {
+        WindowedSender {
+            config,
+            buffer,
+            sessions: BTreeMap::new(),
+            resend: VecDeque::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn config(&self) -> &WindowConfig {
+        &self.config
+    }
+
+    /// Queues the fragments of a message to be sent with `session_id` along
+    /// `routing_header`, replacing any session previously stored with the same
+    /// id. No fragment is sent before the next `poll_next_packets`.
+    ///
+    /// # Parameters
+    /// - `session_id`: Session id shared by all the packets of the message.
+    /// - `fragments`: Fragments of the message, e.g. the ones returned by
+    ///   `Assembler::disassemble`.
+    /// - `routing_header`: Route to the destination, starting with the sending node.
+    pub fn insert(
+        &mut self,
+        session_id: u64,
+        fragments: Vec<Fragment>,
+        routing_header: &SourceRoutingHeader,
+    ) {
+        let window = self
+            .config
+            .initial_window
+            .min(self.config.max_window)
+            .max(self.config.min_window)
+            .max(1);
+        let session = WindowedSession {
+            routing_header: routing_header.clone(),
+            total_n_fragments: fragments.len() as u64,
+            next_fragment: 0,
+            in_flight: 0,
+            window,
+            acks_in_window: 0,
+            given_up: false,
+        };
+        self.buffer.insert_queued(session_id, fragments);
+        self.sessions.insert(session_id, session);
+        self.resend.retain(|(id, _)| *id != session_id);
+    }
+
+    /// Replaces the route of a session, e.g. after `ResendDecision::Reroute`.
+    /// Packets returned by later polls travel along the new route.
+    ///
+    /// # Returns
+    /// Returns `false` if the session is unknown.
+    pub fn set_route(&mut self, session_id: u64, routing_header: &SourceRoutingHeader) -> bool {
+        let Some(session) = self.sessions.get_mut(&session_id) else {
+            return false;
+        };
+        session.routing_header = routing_header.clone();
+        true
+    }
+
+    /// Marks the fragment named by `ack` as acknowledged, freeing its slot in the
+    /// window and growing the window.
+    ///
+    /// # Returns
+    /// Returns `AckOutcome::Completed` when the whole session is acknowledged, in
+    /// which case it is removed from the sender.
+    pub fn handle_ack(&mut self, session_id: u64, ack: &Ack) -> AckOutcome {
+        let outcome = self.buffer.handle_ack(session_id, ack);
+        match outcome {
+            AckOutcome::Pending => {
+                if let Some(session) = self.sessions.get_mut(&session_id) {
+                    session.in_flight = session.in_flight.saturating_sub(1);
+                    session.increase(&self.config);
+                }
+            }
+            AckOutcome::Completed => {
+                self.sessions.remove(&session_id);
+            }
+            AckOutcome::Ignored => {}
+        }
+        outcome
+    }
+
+    /// Asks the resend policy what to do with the fragment named by `nack` and
+    /// queues it to be resent by the next poll, unless the policy gives up or it
+    /// is already queued. The window is halved if the fragment was dropped.
+    ///
+    /// After `ResendDecision::Reroute` the route should be replaced with
+    /// `set_route` before polling. After `ResendDecision::GiveUp` no fragment of
+    /// the session is sent anymore.
+    ///
+    /// # Returns
+    /// Returns the decision of the resend policy, or `None` if the session or the
+    /// fragment is unknown, was never sent or was already acknowledged.
+    pub fn handle_nack(&mut self, session_id: u64, nack: &Nack) -> Option<ResendDecision> {
+        let (decision, _) = self.buffer.decide_nack(session_id, nack)?;
+        let session = self.sessions.get_mut(&session_id)?;
+        if matches!(nack.nack_type, NackType::Dropped) {
+            session.decrease(&self.config);
+        }
+        if decision == ResendDecision::GiveUp {
+            session.given_up = true;
+        } else if !self.resend.contains(&(session_id, nack.fragment_index)) {
+            self.resend.push_back((session_id, nack.fragment_index));
+        }
+        Some(decision)
+    }
+
+    /// Collects the packets to be sent now: fragments named by `Nack`s, fragments
+    /// whose retransmission timeout expired and new fragments fitting into the
+    /// window of their session, in this order. Meant to be called by the node's
+    /// event loop after handling incoming packets and whenever `next_timeout`
+    /// is reached.
+    ///
+    /// # Returns
+    /// Returns `MsgFragment` packets routed along the current route of their session.
+    pub fn poll_next_packets(&mut self) -> Vec<Packet> {
+        let mut packets = Vec::new();
+        while let Some((session_id, fragment_index)) = self.resend.pop_front() {
+            let Some(session) = self.sessions.get(&session_id) else {
+                continue;
+            };
+            let Some(outgoing) = self.buffer.session(session_id) else {
+                continue;
+            };
+            if session.given_up || outgoing.is_fragment_acknowledged(fragment_index) {
+                continue;
+            }
+            if let Some(fragment) = outgoing.fragment(fragment_index) {
+                packets.push(packets::fragment_packet(
+                    fragment.clone(),
+                    &session.routing_header,
+                    session_id,
+                ));
+            }
+        }
+
+        for event in self.buffer.poll_timeouts() {
+            match event {
+                TimerEvent::Retransmit {
+                    session_id,
+                    fragment,
+                } => {
+                    if let Some(session) = self.sessions.get(&session_id) {
+                        if !session.given_up {
+                            packets.push(packets::fragment_packet(
+                                fragment,
+                                &session.routing_header,
+                                session_id,
+                            ));
+                        }
+                    }
+                }
+                TimerEvent::GiveUp { session_id, .. } => {
+                    if let Some(session) = self.sessions.get_mut(&session_id) {
+                        session.given_up = true;
+                    }
+                }
+            }
+        }
+
+        for (&session_id, session) in &mut self.sessions {
+            while !session.given_up
+                && session.in_flight < session.window
+                && session.next_fragment < session.total_n_fragments
+            {
+                let fragment_index = session.next_fragment;
+                session.next_fragment += 1;
+                let Some(outgoing) = self.buffer.session(session_id) else {
+                    break;
+                };
+                if outgoing.is_fragment_acknowledged(fragment_index) {
+                    continue;
+                }
+                let Some(fragment) = outgoing.fragment(fragment_index).cloned() else {
+                    break;
+                };
+                self.buffer.mark_sent(session_id, fragment_index);
+                session.in_flight += 1;
+                packets.push(packets::fragment_packet(
+                    fragment,
+                    &session.routing_header,
+                    session_id,
+                ));
+            }
+        }
+        packets
+    }
+
+    /// Returns the earliest time at which a fragment must be retransmitted, see
+    /// `SendBuffer::next_timeout`.
+    #[must_use]
+    pub fn next_timeout(&self) -> Option<Instant> {
+        self.buffer.next_timeout()
+    }
+
+    /// Returns the current window of a session, in fragments.
+    #[must_use]
+    pub fn window(&self, session_id: u64) -> Option<usize> {
+        self.sessions.get(&session_id).map(|session| session.window)
+    }
+
+    /// Returns the number of fragments of a session sent and not acknowledged yet.
+    #[must_use]
+    pub fn in_flight(&self, session_id: u64) -> Option<usize> {
+        self.sessions
+            .get(&session_id)
+            .map(|session| session.in_flight)
+    }
+
+    /// Returns `true` if a fragment of the session was given up, either by the
+    /// resend policy or after too many retransmissions. The session is kept until
+    /// it is `remove`d.
+    #[must_use]
+    pub fn is_given_up(&self, session_id: u64) -> bool {
+        self.sessions
+            .get(&session_id)
+            .is_some_and(|session| session.given_up)
+    }
+
+    /// Returns the buffer holding the fragments of every session.
+    #[must_use]
+    pub fn send_buffer(&self) -> &SendBuffer<P, C> {
+        &self.buffer
+    }
+
+    /// Stops sending the session stored with `session_id`.
+    ///
+    /// # Returns
+    /// Returns `false` if the session is unknown.
+    pub fn remove(&mut self, session_id: u64) -> bool {
+        self.buffer.remove(session_id);
+        self.resend.retain(|(id, _)| *id != session_id);
+        self.sessions.remove(&session_id).is_some()
+    }
+
+    /// Returns the number of sessions not fully acknowledged.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Returns `true` if no session is awaiting acknowledgement.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+}
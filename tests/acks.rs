@@ -0,0 +1,203 @@
+#[cfg(test)]
+mod tests {
+
+    use std::time::Duration;
+
+    use assembler::clock::ManualClock;
+    use assembler::config::AssemblerConfig;
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::reassembly_buffer::{PacketOutcome, ReassemblyBuffer};
+    use assembler::{Assembler, AssemblerError};
+    use wg_2024::network::SourceRoutingHeader;
+    use wg_2024::packet::{Nack, NackType, Packet, PacketType};
+
+    /// Packets from client 1 to server 9 through drones 4 and 6, as received by
+    /// the server.
+    fn received_packets(bytes: &[u8], session_id: u64) -> Vec<Packet> {
+        let routing_header = SourceRoutingHeader {
+            hop_index: 0,
+            hops: vec![1, 4, 6, 9],
+        };
+        let mut packets =
+            NaiveAssembler::disassemble_to_packets(bytes, &routing_header, session_id);
+        for packet in &mut packets {
+            packet.routing_header.hop_index = 3;
+        }
+        packets
+    }
+
+    fn assert_ack(outcome: &PacketOutcome, session_id: u64, fragment_index: u64) {
+        let ack = outcome.ack.as_ref().expect("fragment is acknowledged");
+        assert_eq!(ack.routing_header.hop_index, 1);
+        assert_eq!(ack.routing_header.hops, [9, 6, 4, 1]);
+        assert_eq!(ack.session_id, session_id);
+        let PacketType::Ack(ack) = &ack.pack_type else {
+            panic!("expected an Ack packet");
+        };
+        assert_eq!(ack.fragment_index, fragment_index);
+    }
+
+    #[test]
+    fn acknowledge_every_fragment() {
+        let bytes = "Hello, World".repeat(30).into_bytes();
+        let packets = received_packets(&bytes, 11);
+
+        let mut buffer = ReassemblyBuffer::new();
+        for (index, packet) in packets.iter().enumerate().rev() {
+            let outcome = buffer.insert_packet(packet);
+            assert_ack(&outcome, 11, index as u64);
+            if index == 0 {
+                assert_eq!(outcome.result, Ok(Some(bytes.clone())));
+            } else {
+                assert_eq!(outcome.result, Ok(None));
+            }
+        }
+    }
+
+    #[test]
+    fn acknowledge_duplicates_after_completion() {
+        let packets = received_packets(&[1; 300], 2);
+
+        let mut buffer = ReassemblyBuffer::new();
+        for packet in &packets {
+            buffer.insert_packet(packet);
+        }
+        assert!(buffer.is_empty());
+
+        // The Ack of the last fragment was lost and the sender retransmits it
+        let outcome = buffer.insert_packet(&packets[2]);
+        assert_ack(&outcome, 2, 2);
+        assert_eq!(outcome.result, Ok(None));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn acknowledge_duplicates_before_completion() {
+        let packets = received_packets(&[1; 300], 2);
+
+        // The default policy rejects duplicates, yet a retransmission is acknowledged
+        let mut buffer = ReassemblyBuffer::new();
+        buffer.insert_packet(&packets[1]);
+        let outcome = buffer.insert_packet(&packets[1]);
+        assert_ack(&outcome, 2, 1);
+        assert_eq!(outcome.result, Ok(None));
+        assert_eq!(buffer.missing(1, 2).unwrap().count(), 2);
+
+        let mut conflicting = packets[1].clone();
+        if let PacketType::MsgFragment(fragment) = &mut conflicting.pack_type {
+            fragment.data[0] = 2;
+        }
+        let outcome = buffer.insert_packet(&conflicting);
+        assert!(outcome.ack.is_none());
+        assert_eq!(outcome.result, Err(AssemblerError::DuplicateIndex(1)));
+
+        buffer.insert_packet(&packets[0]);
+        assert_eq!(
+            buffer.insert_packet(&packets[2]).result,
+            Ok(Some(vec![1; 300]))
+        );
+    }
+
+    #[test]
+    fn do_not_acknowledge_corrupted_message() {
+        let routing_header = SourceRoutingHeader {
+            hop_index: 0,
+            hops: vec![1, 9],
+        };
+        let mut packets =
+            NaiveAssembler::disassemble_to_packets_checked(&[1; 300], &routing_header, 6);
+        if let PacketType::MsgFragment(fragment) = &mut packets[0].pack_type {
+            fragment.data[0] = 2;
+        }
+
+        let mut buffer = ReassemblyBuffer::with_checksum();
+        assert!(buffer.insert_packet(&packets[0]).ack.is_some());
+        assert!(buffer.insert_packet(&packets[1]).ack.is_some());
+        let outcome = buffer.insert_packet(&packets[2]);
+        assert!(outcome.ack.is_none());
+        assert!(matches!(
+            outcome.result,
+            Err(AssemblerError::ChecksumMismatch { .. })
+        ));
+
+        // The retransmitted fragment reports the failure again instead of
+        // starting a new message that would be acknowledged
+        let outcome = buffer.insert_packet(&packets[2]);
+        assert!(outcome.ack.is_none());
+        assert!(matches!(
+            outcome.result,
+            Err(AssemblerError::ChecksumMismatch { .. })
+        ));
+        assert!(buffer.insert_packet(&packets[0]).ack.is_none());
+        assert!(!buffer.contains(1, 6));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn forget_completed_messages_when_idle() {
+        let clock = ManualClock::new();
+        let config = AssemblerConfig {
+            idle_timeout: Duration::from_secs(10),
+            ..AssemblerConfig::default()
+        };
+        let mut buffer = ReassemblyBuffer::with_clock(config, clock.clone());
+        let packets = received_packets(&[1; 10], 2);
+        assert_eq!(
+            buffer.insert_packet(&packets[0]).result,
+            Ok(Some(vec![1; 10]))
+        );
+
+        clock.advance(Duration::from_secs(10));
+        assert!(buffer.evict_expired().is_empty());
+        // The session is reassembled again, as it is no longer remembered
+        assert_eq!(
+            buffer.insert_packet(&packets[0]).result,
+            Ok(Some(vec![1; 10]))
+        );
+    }
+
+    #[test]
+    fn do_not_acknowledge_rejected_fragments() {
+        let mut packets = received_packets(&[1; 300], 5);
+        if let PacketType::MsgFragment(fragment) = &mut packets[1].pack_type {
+            fragment.length = 200;
+        }
+
+        let mut buffer = ReassemblyBuffer::new();
+        let outcome = buffer.insert_packet(&packets[1]);
+        assert!(outcome.ack.is_none());
+        assert_eq!(
+            outcome.result,
+            Err(AssemblerError::InvalidLength {
+                fragment_index: 1,
+                length: 200
+            })
+        );
+
+        let mut unrouted = packets[0].clone();
+        unrouted.routing_header.hops.clear();
+        assert_eq!(
+            buffer.insert_packet(&unrouted).result,
+            Err(AssemblerError::EmptyRoute)
+        );
+    }
+
+    #[test]
+    fn ignore_other_packets() {
+        let packet = Packet {
+            routing_header: SourceRoutingHeader {
+                hop_index: 2,
+                hops: vec![1, 4, 9],
+            },
+            session_id: 3,
+            pack_type: PacketType::Nack(Nack {
+                fragment_index: 0,
+                nack_type: NackType::Dropped,
+            }),
+        };
+
+        let outcome = ReassemblyBuffer::new().insert_packet(&packet);
+        assert!(outcome.ack.is_none());
+        assert_eq!(outcome.result, Ok(None));
+    }
+}
@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+
+    use assembler::checksum;
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::reassembly_buffer::ReassemblyBuffer;
+    use assembler::{Assembler, AssemblerError};
+    use wg_2024::network::SourceRoutingHeader;
+    use wg_2024::packet::PacketType;
+
+    #[test]
+    fn crc32_known_value() {
+        assert_eq!(checksum::crc32(b""), 0);
+        assert_eq!(checksum::crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn checked_round_trip() {
+        let bytes = "Hello, World".repeat(1000).into_bytes();
+        let fragments = NaiveAssembler::disassemble_checked(&bytes);
+
+        assert_eq!(NaiveAssembler::reassemble_checked(&fragments), Ok(bytes));
+    }
+
+    #[test]
+    fn detect_corrupted_fragment() {
+        let bytes = "Hello, World".repeat(1000).into_bytes();
+        let mut fragments = NaiveAssembler::disassemble_checked(&bytes);
+        fragments[17].data[3] ^= 0x10;
+
+        assert!(matches!(
+            NaiveAssembler::reassemble_checked(&fragments),
+            Err(AssemblerError::ChecksumMismatch {
+                session_id: None,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn report_failing_session() {
+        let routing_header = SourceRoutingHeader {
+            hop_index: 1,
+            hops: vec![1, 11, 21],
+        };
+        let mut packets =
+            NaiveAssembler::disassemble_to_packets_checked(&[1; 500], &routing_header, 1);
+        let mut corrupted =
+            NaiveAssembler::disassemble_to_packets_checked(&[2; 500], &routing_header, 2);
+        if let PacketType::MsgFragment(fragment) = &mut corrupted[2].pack_type {
+            fragment.data[0] = 3;
+        }
+        packets.extend(corrupted);
+
+        let messages = NaiveAssembler::reassemble_packets_checked(&packets);
+        assert_eq!(messages[&(1, 1)], Ok(vec![1; 500]));
+        assert!(matches!(
+            messages[&(1, 2)],
+            Err(AssemblerError::ChecksumMismatch {
+                session_id: Some(2),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn reassembly_buffer_verifies_checksum() {
+        let bytes = vec![7; 1000];
+        let mut fragments = NaiveAssembler::disassemble_checked(&bytes);
+
+        let mut buffer = ReassemblyBuffer::with_checksum();
+        let mut reassembled = None;
+        for fragment in &fragments {
+            reassembled = buffer.insert(4, 8, fragment).unwrap();
+        }
+        assert_eq!(reassembled, Some(bytes));
+
+        fragments[0].data[0] = 0;
+        for fragment in &fragments[1..] {
+            assert_eq!(buffer.insert(4, 9, fragment), Ok(None));
+        }
+        assert!(matches!(
+            buffer.insert(4, 9, &fragments[0]),
+            Err(AssemblerError::ChecksumMismatch {
+                session_id: Some(9),
+                ..
+            })
+        ));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn reject_missing_trailer() {
+        let fragments = NaiveAssembler::disassemble(&[1, 2]);
+
+        assert_eq!(
+            NaiveAssembler::reassemble_checked(&fragments),
+            Err(AssemblerError::MissingChecksum { session_id: None })
+        );
+    }
+}
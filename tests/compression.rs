@@ -0,0 +1,120 @@
+#[cfg(test)]
+mod tests {
+
+    use assembler::compression::{
+        compress, decompress, Compression, CompressionError, FLAG_COMPRESSED, FLAG_STORED,
+    };
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::pipeline::{Checksum, Pipeline};
+    use assembler::stateful_assembler::StatefulAssembler;
+    use assembler::{Assembler, AssemblerError};
+    use messages::{DroneSend, MediaResponse, TextResponse};
+
+    /// Deterministic bytes that do not compress.
+    fn noise(length: usize) -> Vec<u8> {
+        let mut state: u32 = 0x1234_5678;
+        (0..length)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn compress_redundant_json() {
+        let message = TextResponse::Text("Hello, World! ".repeat(500));
+        let json = message.stringify().into_bytes();
+        let compressed = compress(&json);
+
+        assert_eq!(compressed[0], FLAG_COMPRESSED);
+        assert!(compressed.len() * 10 < json.len());
+        assert_eq!(decompress(&compressed), Ok(json));
+    }
+
+    #[test]
+    fn store_incompressible_payloads() {
+        for bytes in [noise(1000), vec![], vec![7], b"abc".to_vec()] {
+            let compressed = compress(&bytes);
+
+            assert_eq!(compressed[0], FLAG_STORED);
+            assert_eq!(compressed[1..], bytes);
+            assert_eq!(decompress(&compressed), Ok(bytes));
+        }
+    }
+
+    #[test]
+    fn round_trip_edge_cases() {
+        let mut distant = noise(70_000);
+        distant.extend_from_slice(&noise(70_000));
+        let cases = [
+            vec![0; 100_000],
+            b"abcd".repeat(1000),
+            [noise(300), vec![1; 300], noise(20)].concat(),
+            // Repetitions farther apart than the match window
+            distant,
+        ];
+
+        for bytes in cases {
+            assert_eq!(decompress(&compress(&bytes)), Ok(bytes));
+        }
+    }
+
+    #[test]
+    fn reject_corrupted_payloads() {
+        let compressed = compress(&[5; 1000]);
+
+        assert_eq!(decompress(&[]), Err(CompressionError::Empty));
+        assert_eq!(
+            decompress(&[9, 1, 2]),
+            Err(CompressionError::UnknownFlag(9))
+        );
+        assert_eq!(
+            decompress(&compressed[..compressed.len() - 1]),
+            Err(CompressionError::Truncated)
+        );
+
+        let mut wrong_length = compressed.clone();
+        wrong_length[8] += 1;
+        assert_eq!(
+            decompress(&wrong_length),
+            Err(CompressionError::LengthMismatch {
+                declared: 1001,
+                actual: 1000
+            })
+        );
+
+        // A match reaching before the start of the payload
+        let invalid_offset = [FLAG_COMPRESSED, 0, 0, 0, 0, 0, 0, 0, 8, 0x10, 5, 2, 0];
+        assert_eq!(
+            decompress(&invalid_offset),
+            Err(CompressionError::InvalidOffset(2))
+        );
+    }
+
+    #[test]
+    fn compression_cuts_fragment_count() {
+        let message = MediaResponse::Media(vec![4; 24000]);
+        let json = message.stringify().into_bytes();
+        let mut pipeline = Pipeline::new().then(Compression).then(Checksum);
+
+        let fragments = pipeline.fragmentize(&json).unwrap();
+        assert!(fragments.len() * 10 < NaiveAssembler::disassemble(&json).len());
+        assert_eq!(pipeline.assemble(&fragments), Ok(json));
+    }
+
+    #[test]
+    fn report_compression_errors() {
+        let mut pipeline = Pipeline::new().then(Compression);
+        let fragments = NaiveAssembler::disassemble(&[3, 1, 2]);
+
+        assert_eq!(
+            pipeline.assemble(&fragments),
+            Err(AssemblerError::Compression(CompressionError::UnknownFlag(
+                3
+            )))
+        );
+    }
+}
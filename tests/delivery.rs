@@ -0,0 +1,156 @@
+#[cfg(test)]
+mod tests {
+
+    use std::time::Duration;
+
+    use assembler::clock::ManualClock;
+    use assembler::config::{SendConfig, WindowConfig};
+    use assembler::delivery::{DeliveryEvent, DeliveryState, DeliveryTracker, FailureReason};
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::resend_policy::{DefaultResendPolicy, ResendDecision};
+    use assembler::send_buffer::SendBuffer;
+    use assembler::windowed_sender::WindowedSender;
+    use assembler::Assembler;
+    use wg_2024::network::SourceRoutingHeader;
+    use wg_2024::packet::{Ack, Nack, NackType};
+
+    fn route() -> SourceRoutingHeader {
+        SourceRoutingHeader {
+            hop_index: 0,
+            hops: vec![1, 4, 9],
+        }
+    }
+
+    fn in_flight(acknowledged: u64, total_n_fragments: u64) -> DeliveryState {
+        DeliveryState::InFlight {
+            acknowledged,
+            total_n_fragments,
+        }
+    }
+
+    #[test]
+    fn report_partial_acks_until_delivered() {
+        let mut tracker = DeliveryTracker::new();
+        let handle = tracker.send(4, NaiveAssembler::disassemble(&[1; 300]), &route());
+        assert_eq!(handle.session_id, 4);
+        assert_eq!(tracker.state(handle), Some(DeliveryState::Queued));
+
+        assert_eq!(tracker.poll_next_packets().len(), 3);
+        assert_eq!(tracker.state(handle), Some(in_flight(0, 3)));
+
+        tracker.handle_ack(4, &Ack { fragment_index: 2 });
+        tracker.handle_ack(4, &Ack { fragment_index: 0 });
+        assert_eq!(tracker.state(handle), Some(in_flight(2, 3)));
+        // A duplicate Ack does not count twice
+        tracker.handle_ack(4, &Ack { fragment_index: 0 });
+        assert_eq!(tracker.state(handle), Some(in_flight(2, 3)));
+
+        tracker.handle_ack(4, &Ack { fragment_index: 1 });
+        assert_eq!(tracker.state(handle), Some(DeliveryState::Delivered));
+        assert_eq!(tracker.forget(handle), Some(DeliveryState::Delivered));
+        assert_eq!(tracker.state(handle), None);
+    }
+
+    #[test]
+    fn fail_on_terminal_nack() {
+        let buffer = SendBuffer::with_policy(DefaultResendPolicy { max_nacks: 2 });
+        let sender = WindowedSender::with_send_buffer(WindowConfig::default(), buffer);
+        let mut tracker = DeliveryTracker::with_sender(sender);
+        let handle = tracker.send(4, NaiveAssembler::disassemble(&[1; 300]), &route());
+        tracker.poll_next_packets();
+        tracker.handle_ack(4, &Ack { fragment_index: 0 });
+
+        let nack = Nack {
+            fragment_index: 1,
+            nack_type: NackType::ErrorInRouting(4),
+        };
+        assert!(matches!(
+            tracker.handle_nack(4, &nack),
+            Some(ResendDecision::Reroute { .. })
+        ));
+        assert_eq!(tracker.state(handle), Some(in_flight(1, 3)));
+        assert_eq!(tracker.handle_nack(4, &nack), Some(ResendDecision::GiveUp));
+
+        let state = tracker.state(handle).unwrap();
+        assert_eq!(
+            state,
+            DeliveryState::Failed(FailureReason::ErrorInRouting(4))
+        );
+        assert_eq!(state.to_string(), "failed: route error at node 4");
+        // Nothing of the failed message is sent anymore
+        assert!(tracker.poll_next_packets().is_empty());
+        assert!(tracker.sender().is_empty());
+    }
+
+    #[test]
+    fn fail_after_retransmissions_time_out() {
+        let clock = ManualClock::new();
+        let config = SendConfig {
+            max_retries: 1,
+            ..SendConfig::default()
+        };
+        let buffer = SendBuffer::with_clock(DefaultResendPolicy::default(), config, clock.clone());
+        let sender = WindowedSender::with_send_buffer(WindowConfig::default(), buffer);
+        let mut tracker = DeliveryTracker::with_sender(sender);
+        let handle = tracker.send(2, NaiveAssembler::disassemble(&[1; 10]), &route());
+        tracker.poll_next_packets();
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(tracker.poll_next_packets().len(), 1);
+        clock.advance(Duration::from_secs(2));
+        assert!(tracker.poll_next_packets().is_empty());
+        assert_eq!(
+            tracker.state(handle),
+            Some(DeliveryState::Failed(FailureReason::Timeout))
+        );
+        assert_eq!(tracker.next_timeout(), None);
+    }
+
+    #[test]
+    fn publish_transitions_to_subscribers() {
+        let mut tracker = DeliveryTracker::new();
+        let events = tracker.subscribe();
+        let first = tracker.send(1, NaiveAssembler::disassemble(&[1; 200]), &route());
+        let second = tracker.send(2, NaiveAssembler::disassemble(&[1; 10]), &route());
+        tracker.poll_next_packets();
+        tracker.handle_ack(1, &Ack { fragment_index: 1 });
+        tracker.handle_ack(2, &Ack { fragment_index: 0 });
+        assert!(tracker.cancel(first));
+        assert!(!tracker.cancel(second));
+
+        let received: Vec<DeliveryEvent> = events.try_iter().collect();
+        let expected = [
+            (first, DeliveryState::Queued),
+            (second, DeliveryState::Queued),
+            (first, in_flight(0, 2)),
+            (second, in_flight(0, 1)),
+            (first, in_flight(1, 2)),
+            (second, DeliveryState::Delivered),
+            (first, DeliveryState::Failed(FailureReason::Cancelled)),
+        ];
+        assert_eq!(received.len(), expected.len());
+        for (event, (handle, state)) in received.iter().zip(expected) {
+            assert_eq!(event.handle, handle);
+            assert_eq!(event.state, state);
+        }
+
+        // Subscribers that went away are no longer notified
+        drop(events);
+        tracker.send(3, NaiveAssembler::disassemble(&[1; 10]), &route());
+    }
+
+    #[test]
+    fn cancel_message_replaced_by_same_session() {
+        let mut tracker = DeliveryTracker::new();
+        let first = tracker.send(1, NaiveAssembler::disassemble(&[1; 10]), &route());
+        let second = tracker.send(1, NaiveAssembler::disassemble(&[2; 10]), &route());
+
+        assert_ne!(first.message_id, second.message_id);
+        assert_eq!(
+            tracker.state(first),
+            Some(DeliveryState::Failed(FailureReason::Cancelled))
+        );
+        assert_eq!(tracker.state(second), Some(DeliveryState::Queued));
+        assert_eq!(tracker.forget(second), None);
+    }
+}
@@ -0,0 +1,123 @@
+#[cfg(test)]
+mod tests {
+
+    use assembler::config::{AssemblerConfig, DuplicatePolicy};
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::reassembly_buffer::ReassemblyBuffer;
+    use assembler::validation::deduplicate;
+    use assembler::{Assembler, AssemblerError};
+
+    fn buffer_with_policy(duplicate_policy: DuplicatePolicy) -> ReassemblyBuffer {
+        ReassemblyBuffer::with_config(AssemblerConfig {
+            duplicate_policy,
+            ..AssemblerConfig::default()
+        })
+    }
+
+    #[test]
+    fn reject_duplicates_by_default() {
+        let mut fragments = NaiveAssembler::disassemble(&[1; 300]);
+        fragments.push(fragments[1].clone());
+
+        assert_eq!(
+            NaiveAssembler::try_reassemble_with_policy(&fragments, DuplicatePolicy::Reject),
+            Err(AssemblerError::DuplicateIndex(1))
+        );
+        assert_eq!(
+            deduplicate(&fragments, DuplicatePolicy::Reject).err(),
+            Some(AssemblerError::DuplicateIndex(1))
+        );
+    }
+
+    #[test]
+    fn ignore_retransmitted_fragments() {
+        let bytes = "Hello, World".repeat(100).into_bytes();
+        let mut fragments = NaiveAssembler::disassemble(&bytes);
+        let retransmitted = fragments.clone();
+        // Even a conflicting duplicate is ignored, the first fragment wins
+        let mut conflicting = fragments[0].clone();
+        conflicting.data[0] = 0;
+        fragments.push(conflicting);
+        fragments.extend(retransmitted);
+
+        assert_eq!(
+            NaiveAssembler::try_reassemble_with_policy(&fragments, DuplicatePolicy::Ignore),
+            Ok(bytes)
+        );
+    }
+
+    #[test]
+    fn verify_identical_duplicates() {
+        let bytes = "Hello, World".repeat(100).into_bytes();
+        let mut fragments = NaiveAssembler::disassemble(&bytes);
+        fragments.push(fragments[3].clone());
+
+        assert_eq!(
+            NaiveAssembler::try_reassemble_with_policy(
+                &fragments,
+                DuplicatePolicy::VerifyIdentical
+            ),
+            Ok(bytes)
+        );
+
+        let mut conflicting = fragments[4].clone();
+        conflicting.length -= 1;
+        fragments.push(conflicting);
+        assert_eq!(
+            NaiveAssembler::try_reassemble_with_policy(
+                &fragments,
+                DuplicatePolicy::VerifyIdentical
+            ),
+            Err(AssemblerError::ConflictingDuplicate { fragment_index: 4 })
+        );
+    }
+
+    #[test]
+    fn buffer_is_idempotent_under_retransmission() {
+        let fragments = NaiveAssembler::disassemble(&[5; 300]);
+
+        for policy in [DuplicatePolicy::Ignore, DuplicatePolicy::VerifyIdentical] {
+            let mut buffer = buffer_with_policy(policy);
+            assert_eq!(buffer.insert(1, 0, &fragments[0]), Ok(None));
+            assert_eq!(buffer.insert(1, 0, &fragments[0]), Ok(None));
+            assert_eq!(buffer.insert(1, 0, &fragments[1]), Ok(None));
+            assert_eq!(buffer.insert(1, 0, &fragments[1]), Ok(None));
+            assert_eq!(buffer.insert(1, 0, &fragments[2]), Ok(Some(vec![5; 300])));
+
+            // The Ack of the last fragment was lost and the sender retransmits it
+            assert_eq!(buffer.insert(1, 0, &fragments[2]), Ok(None));
+            assert!(buffer.is_empty());
+            assert_eq!(buffer.buffered_bytes(), 0);
+        }
+
+        // Retransmissions after completion are dropped even when rejecting duplicates
+        let mut buffer = buffer_with_policy(DuplicatePolicy::Reject);
+        assert_eq!(buffer.insert(1, 0, &fragments[0]), Ok(None));
+        assert_eq!(
+            buffer.insert(1, 0, &fragments[0]),
+            Err(AssemblerError::DuplicateIndex(0))
+        );
+        assert_eq!(buffer.insert(1, 0, &fragments[1]), Ok(None));
+        assert_eq!(buffer.insert(1, 0, &fragments[2]), Ok(Some(vec![5; 300])));
+        assert_eq!(buffer.insert(1, 0, &fragments[2]), Ok(None));
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.buffered_bytes(), 0);
+    }
+
+    #[test]
+    fn buffer_flags_conflicting_duplicate() {
+        let fragments = NaiveAssembler::disassemble(&[5; 300]);
+        let mut conflicting = fragments[2].clone();
+        conflicting.data[10] = 6;
+
+        let mut buffer = buffer_with_policy(DuplicatePolicy::VerifyIdentical);
+        assert_eq!(buffer.insert(1, 0, &fragments[2]), Ok(None));
+        assert_eq!(buffer.insert(1, 0, &fragments[2]), Ok(None));
+        assert_eq!(
+            buffer.insert(1, 0, &conflicting),
+            Err(AssemblerError::ConflictingDuplicate { fragment_index: 2 })
+        );
+        assert_eq!(buffer.insert(1, 0, &fragments[0]), Ok(None));
+        assert_eq!(buffer.insert(1, 0, &fragments[1]), Ok(Some(vec![5; 300])));
+    }
+}
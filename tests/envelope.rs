@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+
+    use std::error::Error;
+
+    use assembler::envelope::{self, EnvelopeError, MessageKind, Tagged, HEADER_SIZE};
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::{Assembler, AssemblerError};
+    use messages::{ChatRequest, DroneSend, MediaResponse, TextResponse};
+
+    #[test]
+    fn dispatch_on_reassembled_kind() {
+        let message = ChatRequest::Register(8);
+        let json = message.stringify();
+        let fragments = NaiveAssembler::disassemble_enveloped(ChatRequest::KIND, json.as_bytes());
+
+        let (kind, bytes) = NaiveAssembler::reassemble_enveloped(&fragments).unwrap();
+        assert_eq!(kind, MessageKind::ChatRequest);
+        // The payload can be parsed directly with the right type
+        let json = String::from_utf8(bytes).unwrap();
+        assert_eq!(ChatRequest::from_string(json).unwrap(), message);
+    }
+
+    #[test]
+    fn envelope_round_trip_long_payload() {
+        let message = MediaResponse::Media(vec![4; 24000]);
+        let json = message.stringify();
+        let fragments = NaiveAssembler::disassemble_enveloped(MediaResponse::KIND, json.as_bytes());
+
+        assert_eq!(
+            NaiveAssembler::reassemble_enveloped(&fragments),
+            Ok((MessageKind::MediaResponse, json.into_bytes()))
+        );
+    }
+
+    #[test]
+    fn reject_foreign_payload() {
+        // A plain JSON message carries no envelope
+        let message = TextResponse::Text("This as a test string.".to_string());
+        let fragments = NaiveAssembler::disassemble_message(&message);
+
+        assert!(matches!(
+            NaiveAssembler::reassemble_enveloped(&fragments),
+            Err(AssemblerError::Envelope(EnvelopeError::BadMagic(_)))
+        ));
+    }
+
+    #[test]
+    fn expose_envelope_error_as_source() {
+        let error = AssemblerError::from(EnvelopeError::UnknownKind(42));
+        let source = error.source().expect("envelope error is chained");
+        assert_eq!(
+            source.downcast_ref::<EnvelopeError>(),
+            Some(&EnvelopeError::UnknownKind(42))
+        );
+    }
+
+    #[test]
+    fn reject_corrupted_header() {
+        let wrapped = envelope::wrap(MessageKind::Raw, b"payload");
+
+        assert_eq!(
+            envelope::unwrap(&wrapped[..HEADER_SIZE - 1]),
+            Err(EnvelopeError::TooShort(HEADER_SIZE - 1))
+        );
+
+        let mut corrupted = wrapped.clone();
+        corrupted[2] = 9;
+        assert_eq!(
+            envelope::unwrap(&corrupted),
+            Err(EnvelopeError::UnsupportedVersion(9))
+        );
+
+        let mut corrupted = wrapped.clone();
+        corrupted[3] = 42;
+        assert_eq!(
+            envelope::unwrap(&corrupted),
+            Err(EnvelopeError::UnknownKind(42))
+        );
+
+        assert_eq!(
+            envelope::unwrap(&wrapped[..wrapped.len() - 1]),
+            Err(EnvelopeError::LengthMismatch {
+                declared: 7,
+                actual: 6
+            })
+        );
+        assert_eq!(
+            envelope::unwrap(&wrapped),
+            Ok((MessageKind::Raw, &b"payload"[..]))
+        );
+    }
+}
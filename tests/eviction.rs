@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod tests {
+
+    use std::time::Duration;
+
+    use assembler::clock::ManualClock;
+    use assembler::config::AssemblerConfig;
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::reassembly_buffer::ReassemblyBuffer;
+    use assembler::Assembler;
+
+    fn buffer_with_timeout(clock: &ManualClock) -> ReassemblyBuffer<ManualClock> {
+        let config = AssemblerConfig {
+            idle_timeout: Duration::from_secs(10),
+            ..AssemblerConfig::default()
+        };
+        ReassemblyBuffer::with_clock(config, clock.clone())
+    }
+
+    #[test]
+    fn evict_idle_partial_message() {
+        let clock = ManualClock::new();
+        let mut buffer = buffer_with_timeout(&clock);
+        let fragments = NaiveAssembler::disassemble(&[1; 300]);
+
+        assert_eq!(buffer.insert(1, 5, &fragments[0]), Ok(None));
+        clock.advance(Duration::from_secs(9));
+        assert!(buffer.evict_expired().is_empty());
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(buffer.evict_expired(), vec![(1, 5)]);
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.buffered_bytes(), 0);
+    }
+
+    #[test]
+    fn new_fragments_keep_message_alive() {
+        let clock = ManualClock::new();
+        let mut buffer = buffer_with_timeout(&clock);
+        let fragments = NaiveAssembler::disassemble(&[1; 300]);
+        let other = NaiveAssembler::disassemble(&[2; 300]);
+
+        assert_eq!(buffer.insert(1, 5, &fragments[0]), Ok(None));
+        assert_eq!(buffer.insert(2, 5, &other[0]), Ok(None));
+        clock.advance(Duration::from_secs(6));
+        assert_eq!(buffer.insert(1, 5, &fragments[1]), Ok(None));
+        clock.advance(Duration::from_secs(6));
+
+        // Only the message that stayed idle is evicted
+        assert_eq!(buffer.evict_expired(), vec![(2, 5)]);
+        assert_eq!(buffer.insert(1, 5, &fragments[2]), Ok(Some(vec![1; 300])));
+    }
+}
@@ -0,0 +1,151 @@
+#[cfg(test)]
+mod tests {
+
+    use assembler::compression::Compression;
+    use assembler::fec::{FecAssembler, FecError};
+    use assembler::pipeline::Pipeline;
+    use assembler::stateful_assembler::StatefulAssembler;
+    use assembler::AssemblerError;
+    use messages::{DroneSend, MediaResponse};
+    use wg_2024::packet::Fragment;
+
+    /// Deterministic xorshift generator, so that failures can be reproduced.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, bound: usize) -> usize {
+            (self.next() % bound as u64) as usize
+        }
+    }
+
+    fn bytes(rng: &mut Rng, length: usize) -> Vec<u8> {
+        (0..length).map(|_| rng.next() as u8).collect()
+    }
+
+    /// Drops `losses` random fragments of every block of `block_size` fragments.
+    fn drop_per_block(
+        rng: &mut Rng,
+        fragments: Vec<Fragment>,
+        block_size: usize,
+        losses: usize,
+    ) -> Vec<Fragment> {
+        let mut kept = Vec::new();
+        for block in fragments.chunks(block_size) {
+            let mut block = block.to_vec();
+            for _ in 0..losses.min(block.len()) {
+                block.remove(rng.below(block.len()));
+            }
+            kept.extend(block);
+        }
+        // Delivery order does not matter
+        for index in (1..kept.len()).rev() {
+            kept.swap(index, rng.below(index + 1));
+        }
+        kept
+    }
+
+    #[test]
+    fn recover_random_losses() {
+        let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+        for (data_per_block, parity_per_block) in [(1, 1), (4, 2), (10, 3), (200, 56)] {
+            let mut fec = FecAssembler::new(data_per_block, parity_per_block);
+            for length in [0, 1, 120, 128, 1000, 30_000] {
+                let bytes = bytes(&mut rng, length);
+                let fragments = fec.fragmentize(&bytes).unwrap();
+                let block_size = data_per_block + parity_per_block;
+
+                let received = drop_per_block(&mut rng, fragments, block_size, parity_per_block);
+                assert_eq!(fec.assemble(&received), Ok(bytes));
+            }
+        }
+    }
+
+    #[test]
+    fn recover_large_media_response() {
+        let mut rng = Rng(42);
+        let message = MediaResponse::Media(bytes(&mut rng, 200_000));
+        let json = message.stringify().into_bytes();
+        let mut fec = FecAssembler::new(32, 4);
+
+        let fragments = fec.fragmentize(&json).unwrap();
+        assert_eq!(fragments.len() as u64, fragments[0].total_n_fragments);
+        // Independent losses of about 1% of the fragments
+        let received: Vec<Fragment> = fragments
+            .into_iter()
+            .filter(|_| rng.below(100) >= 1)
+            .collect();
+
+        let Ok(bytes) = fec.assemble(&received) else {
+            panic!("losses exceeded the parity of a block");
+        };
+        assert_eq!(
+            MediaResponse::from_string(String::from_utf8(bytes).unwrap()),
+            Ok(message)
+        );
+    }
+
+    #[test]
+    fn report_unrecoverable_blocks() {
+        let mut fec = FecAssembler::new(4, 2);
+        let fragments = fec.fragmentize(&[7; 2000]).unwrap();
+
+        // Three fragments of the second block are lost
+        let received: Vec<Fragment> = fragments
+            .iter()
+            .filter(|fragment| !(6..9).contains(&fragment.fragment_index))
+            .cloned()
+            .collect();
+        assert_eq!(
+            fec.assemble(&received),
+            Err(AssemblerError::Fec(FecError::Unrecoverable {
+                block: 1,
+                received: 3,
+                required: 4
+            }))
+        );
+        // Dropping only parity fragments never loses data
+        let data_only: Vec<Fragment> = fragments
+            .iter()
+            .filter(|fragment| fragment.fragment_index % 6 < 4)
+            .cloned()
+            .collect();
+        assert_eq!(fec.assemble(&data_only), Ok(vec![7; 2000]));
+    }
+
+    #[test]
+    fn reject_foreign_layout() {
+        let fragments = FecAssembler::new(4, 2).fragmentize(&[1; 300]).unwrap();
+
+        assert_eq!(fragments.len(), 5);
+        assert_eq!(
+            FecAssembler::new(2, 2).assemble(&fragments),
+            Err(AssemblerError::Fec(FecError::InvalidLayout {
+                total_n_fragments: 5
+            }))
+        );
+        assert_eq!(
+            FecAssembler::new(4, 2).assemble(&[]),
+            Err(AssemblerError::Empty)
+        );
+    }
+
+    #[test]
+    fn combine_with_compression() {
+        let json = MediaResponse::Media(vec![4; 24000])
+            .stringify()
+            .into_bytes();
+        let mut pipeline =
+            Pipeline::with_assembler(Box::new(FecAssembler::new(8, 2))).then(Compression);
+
+        let mut fragments = pipeline.fragmentize(&json).unwrap();
+        fragments.remove(0);
+        assert_eq!(pipeline.assemble(&fragments), Ok(json));
+    }
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+
+    use std::io::{Cursor, ErrorKind};
+
+    use assembler::fragment_iter::{disassemble_iter, disassemble_reader};
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::Assembler;
+    use wg_2024::packet::Fragment;
+
+    fn fields(fragments: &[Fragment]) -> Vec<(u64, u64, u8, Vec<u8>)> {
+        fragments
+            .iter()
+            .map(|f| {
+                (
+                    f.fragment_index,
+                    f.total_n_fragments,
+                    f.length,
+                    f.data.to_vec(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn iterator_matches_disassemble() {
+        for size in [0, 1, 127, 128, 129, 1000] {
+            let bytes: Vec<u8> = (0..size).map(|i| i as u8).collect();
+            let lazy: Vec<Fragment> = disassemble_iter(&bytes).collect();
+
+            assert_eq!(fields(&lazy), fields(&NaiveAssembler::disassemble(&bytes)));
+        }
+    }
+
+    #[test]
+    fn iterator_has_exact_size() {
+        let bytes = [7; 300];
+        let mut fragments = disassemble_iter(&bytes);
+        assert_eq!(fragments.len(), 3);
+
+        fragments.next();
+        assert_eq!(fragments.len(), 2);
+        assert_eq!(fragments.last().unwrap().length, 44);
+        assert_eq!(disassemble_iter(&[]).len(), 1);
+    }
+
+    #[test]
+    fn reader_matches_disassemble() {
+        let bytes = "Hello, World".repeat(50).into_bytes();
+        let fragments: Vec<Fragment> = disassemble_reader(Cursor::new(&bytes), bytes.len() as u64)
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(
+            fields(&fragments),
+            fields(&NaiveAssembler::disassemble(&bytes))
+        );
+        assert_eq!(NaiveAssembler::reassemble(&fragments), bytes);
+    }
+
+    #[test]
+    fn reader_stops_on_short_input() {
+        let bytes = [1; 200];
+        let mut fragments = disassemble_reader(Cursor::new(&bytes), 300);
+
+        assert_eq!(fragments.next().unwrap().unwrap().length, 128);
+        assert_eq!(
+            fragments.next().unwrap().unwrap_err().kind(),
+            ErrorKind::UnexpectedEof
+        );
+        assert!(fragments.next().is_none());
+    }
+}
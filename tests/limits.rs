@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod tests {
+
+    use assembler::config::{AssemblerConfig, Limit};
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::reassembly_buffer::ReassemblyBuffer;
+    use assembler::{Assembler, AssemblerError};
+    use wg_2024::packet::{Fragment, FRAGMENT_DSIZE};
+
+    fn forged_fragment(total_n_fragments: u64) -> Fragment {
+        Fragment {
+            fragment_index: 0,
+            total_n_fragments,
+            length: FRAGMENT_DSIZE as u8,
+            data: [0; FRAGMENT_DSIZE],
+        }
+    }
+
+    #[test]
+    fn reject_huge_total_n_fragments() {
+        let mut buffer = ReassemblyBuffer::new();
+
+        // Would need 2^64 * 128 bytes if preallocated blindly
+        assert_eq!(
+            buffer.insert(1, 0, &forged_fragment(u64::MAX)),
+            Err(AssemblerError::LimitExceeded(Limit::FragmentsPerMessage {
+                limit: AssemblerConfig::default().max_fragments_per_message,
+                requested: u64::MAX
+            }))
+        );
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.buffered_bytes(), 0);
+    }
+
+    #[test]
+    fn reject_message_larger_than_max_size() {
+        let config = AssemblerConfig {
+            max_message_size: 1000,
+            ..AssemblerConfig::default()
+        };
+        let mut buffer = ReassemblyBuffer::with_config(config);
+
+        assert_eq!(
+            buffer.insert(1, 0, &forged_fragment(8)),
+            Err(AssemblerError::LimitExceeded(Limit::MessageSize {
+                limit: 1000,
+                requested: 1024
+            }))
+        );
+        // Seven full fragments still fit
+        assert_eq!(buffer.insert(1, 0, &forged_fragment(7)), Ok(None));
+    }
+
+    #[test]
+    fn limit_sessions_per_source() {
+        let config = AssemblerConfig {
+            max_sessions_per_source: 2,
+            ..AssemblerConfig::default()
+        };
+        let mut buffer = ReassemblyBuffer::with_config(config);
+
+        assert_eq!(buffer.insert(1, 0, &forged_fragment(2)), Ok(None));
+        assert_eq!(buffer.insert(1, 1, &forged_fragment(2)), Ok(None));
+        assert_eq!(
+            buffer.insert(1, 2, &forged_fragment(2)),
+            Err(AssemblerError::LimitExceeded(Limit::SessionsPerSource {
+                source: 1,
+                limit: 2
+            }))
+        );
+        // Other sources are not affected
+        assert_eq!(buffer.insert(2, 2, &forged_fragment(2)), Ok(None));
+        // Dropping a session frees a slot
+        assert!(buffer.remove(1, 0));
+        assert_eq!(buffer.insert(1, 2, &forged_fragment(2)), Ok(None));
+    }
+
+    #[test]
+    fn limit_total_buffered_bytes() {
+        let config = AssemblerConfig {
+            max_buffered_bytes: 4 * FRAGMENT_DSIZE,
+            ..AssemblerConfig::default()
+        };
+        let mut buffer = ReassemblyBuffer::with_config(config);
+
+        assert_eq!(buffer.insert(1, 0, &forged_fragment(3)), Ok(None));
+        assert_eq!(buffer.buffered_bytes(), 3 * FRAGMENT_DSIZE);
+        assert_eq!(
+            buffer.insert(2, 0, &forged_fragment(2)),
+            Err(AssemblerError::LimitExceeded(Limit::BufferedBytes {
+                limit: 4 * FRAGMENT_DSIZE,
+                requested: 5 * FRAGMENT_DSIZE as u64
+            }))
+        );
+        assert_eq!(
+            buffer.insert(2, 0, &forged_fragment(1)),
+            Ok(Some(vec![0; 128]))
+        );
+    }
+
+    #[test]
+    fn completed_messages_release_buffered_bytes() {
+        let mut buffer = ReassemblyBuffer::new();
+        let fragments = NaiveAssembler::disassemble(&[1; 1000]);
+        for fragment in &fragments[1..] {
+            assert_eq!(buffer.insert(1, 0, fragment), Ok(None));
+        }
+        assert_eq!(buffer.buffered_bytes(), fragments.len() * FRAGMENT_DSIZE);
+
+        assert_eq!(buffer.insert(1, 0, &fragments[0]), Ok(Some(vec![1; 1000])));
+        assert_eq!(buffer.buffered_bytes(), 0);
+    }
+}
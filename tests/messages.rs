@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::{Assembler, AssemblerError};
+    use messages::ChatRequest;
+    use messages::ChatResponse;
+    use messages::MediaRequest;
+    use messages::MediaResponse;
+    use messages::TextRequest;
+    use messages::TextResponse;
+
+    #[test]
+    fn message_round_trip_text_response() {
+        let message = TextResponse::Text("Hello, World".repeat(1000));
+        let fragments = NaiveAssembler::disassemble_message(&message);
+        let deserialized: TextResponse = NaiveAssembler::reassemble_message(&fragments).unwrap();
+
+        assert_eq!(message, deserialized);
+    }
+
+    #[test]
+    fn message_round_trip_media() {
+        let request = MediaRequest::Media(57);
+        let fragments = NaiveAssembler::disassemble_message(&request);
+        assert_eq!(
+            NaiveAssembler::reassemble_message::<MediaRequest>(&fragments),
+            Ok(request)
+        );
+
+        let response = MediaResponse::Media(vec![4; 24000]);
+        let fragments = NaiveAssembler::disassemble_message(&response);
+        assert_eq!(
+            NaiveAssembler::reassemble_message::<MediaResponse>(&fragments),
+            Ok(response)
+        );
+    }
+
+    #[test]
+    fn message_round_trip_chat() {
+        let request = ChatRequest::SendMessage {
+            from: 8,
+            to: 3,
+            message: String::from("Hello this is a test"),
+        };
+        let fragments = NaiveAssembler::disassemble_message(&request);
+        assert_eq!(
+            NaiveAssembler::reassemble_message::<ChatRequest>(&fragments),
+            Ok(request)
+        );
+
+        let response = ChatResponse::ClientList(vec![87, 7, 55, 43, 4, 8]);
+        let fragments = NaiveAssembler::disassemble_message(&response);
+        assert_eq!(
+            NaiveAssembler::reassemble_message::<ChatResponse>(&fragments),
+            Ok(response)
+        );
+    }
+
+    #[test]
+    fn reassemble_message_invalid_utf8() {
+        let fragments = NaiveAssembler::disassemble(&[0xff, 0xfe, 0xfd]);
+
+        assert!(matches!(
+            NaiveAssembler::reassemble_message::<TextRequest>(&fragments),
+            Err(AssemblerError::InvalidUtf8(_))
+        ));
+    }
+
+    #[test]
+    fn reassemble_message_wrong_type() {
+        let fragments = NaiveAssembler::disassemble_message(&TextRequest::Text(74));
+
+        assert!(matches!(
+            NaiveAssembler::reassemble_message::<ChatResponse>(&fragments),
+            Err(AssemblerError::Deserialization(_))
+        ));
+    }
+
+    #[test]
+    fn reassemble_message_malformed_fragments() {
+        assert_eq!(
+            NaiveAssembler::reassemble_message::<TextRequest>(&[]),
+            Err(AssemblerError::Empty)
+        );
+    }
+}
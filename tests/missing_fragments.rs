@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod tests {
+
+    use assembler::missing_fragments::MissingFragments;
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::reassembly_buffer::ReassemblyBuffer;
+    use assembler::Assembler;
+
+    #[test]
+    fn report_missing_ranges() {
+        let fragments = NaiveAssembler::disassemble(&[1; 20 * 128]);
+
+        let mut buffer = ReassemblyBuffer::new();
+        for index in [0, 1, 2, 5, 9, 10, 19] {
+            assert_eq!(buffer.insert(1, 3, &fragments[index]), Ok(None));
+        }
+
+        let missing = buffer.missing(1, 3).unwrap();
+        assert_eq!(missing.total_n_fragments(), 20);
+        assert_eq!(missing.ranges(), &[3..5, 6..9, 11..19]);
+        assert_eq!(missing.count(), 13);
+        assert_eq!(
+            missing.indexes().collect::<Vec<_>>(),
+            vec![3, 4, 6, 7, 8, 11, 12, 13, 14, 15, 16, 17, 18]
+        );
+        assert!(buffer.missing(1, 4).is_none());
+    }
+
+    #[test]
+    fn bitmap_round_trip() {
+        let fragments = NaiveAssembler::disassemble(&[1; 20 * 128]);
+
+        let mut buffer = ReassemblyBuffer::new();
+        for index in [0, 1, 2, 5, 9, 10, 19] {
+            assert_eq!(buffer.insert(1, 3, &fragments[index]), Ok(None));
+        }
+        let missing = buffer.missing(1, 3).unwrap();
+
+        let bitmap = missing.to_bitmap();
+        assert_eq!(bitmap, vec![0b0001_1011, 0b1001_1111, 0b1110_0000]);
+        assert_eq!(MissingFragments::from_bitmap(&bitmap, 20), missing);
+    }
+
+    #[test]
+    fn shrink_as_fragments_arrive() {
+        let fragments = NaiveAssembler::disassemble(&[1; 300]);
+
+        let mut buffer = ReassemblyBuffer::new();
+        assert_eq!(buffer.insert(1, 3, &fragments[1]), Ok(None));
+        assert_eq!(buffer.missing(1, 3).unwrap().ranges(), &[0..1, 2..3]);
+        assert_eq!(buffer.insert(1, 3, &fragments[0]), Ok(None));
+        assert_eq!(
+            buffer.missing(1, 3).unwrap().indexes().collect::<Vec<_>>(),
+            vec![2]
+        );
+        assert_eq!(buffer.insert(1, 3, &fragments[2]), Ok(Some(vec![1; 300])));
+        assert!(buffer.missing(1, 3).is_none());
+    }
+}
@@ -436,4 +436,38 @@ mod tests {
 
         assert_eq!(original_message, deserialized);
     }
+
+    #[test]
+    fn reassemble_very_large_payload_in_linear_time() {
+        // 16 MiB payload, 131072 fragments. Rewriting the tail of the buffer for
+        // every fragment would copy terabytes of memory and never finish.
+        let bytes: Vec<u8> = (0..16 * 1024 * 1024)
+            .map(|i: u32| (i % 251) as u8)
+            .collect();
+        // Create fragments
+        let mut fragments = NaiveAssembler::disassemble(&bytes);
+        // Shuffle fragments deterministically so reassembly cannot rely on ordering
+        fragments.reverse();
+        let half = fragments.len() / 2;
+        fragments[..half].rotate_left(1000);
+        // Reassemble fragments into byte vector
+        let reassembled = NaiveAssembler::reassemble(&fragments);
+
+        assert_eq!(bytes, reassembled);
+    }
+
+    #[test]
+    fn fragment_and_reassemble_empty_payload() {
+        // Create fragments
+        let fragments = NaiveAssembler::disassemble(&[]);
+        // An empty payload is carried by a single empty fragment
+        assert_eq!(fragments.len(), 1);
+        assert_eq!(fragments[0].fragment_index, 0);
+        assert_eq!(fragments[0].total_n_fragments, 1);
+        assert_eq!(fragments[0].length, 0);
+        // Reassemble fragments into byte vector
+        let bytes = NaiveAssembler::reassemble(&fragments);
+
+        assert!(bytes.is_empty());
+    }
 }
@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod tests {
+
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::{Assembler, AssemblerError};
+    use wg_2024::network::SourceRoutingHeader;
+    use wg_2024::packet::{Ack, Packet, PacketType};
+
+    #[test]
+    fn disassemble_to_packets_sets_header_and_session() {
+        let routing_header = SourceRoutingHeader {
+            hop_index: 0,
+            hops: vec![1, 11, 12, 21],
+        };
+        let packets = NaiveAssembler::disassemble_to_packets(&[5; 300], &routing_header, 42);
+
+        assert_eq!(packets.len(), 3);
+        for (index, packet) in packets.iter().enumerate() {
+            assert_eq!(packet.session_id, 42);
+            assert_eq!(packet.routing_header.hop_index, 1);
+            assert_eq!(packet.routing_header.hops, routing_header.hops);
+            let PacketType::MsgFragment(fragment) = &packet.pack_type else {
+                panic!("Expected a fragment packet");
+            };
+            assert_eq!(fragment.fragment_index, index as u64);
+        }
+    }
+
+    #[test]
+    fn reassemble_packets_per_session() {
+        let routing_header = SourceRoutingHeader {
+            hop_index: 1,
+            hops: vec![1, 11, 21],
+        };
+        let first = "First message".repeat(30).into_bytes();
+        let second = "Second message".repeat(20).into_bytes();
+
+        let mut packets = NaiveAssembler::disassemble_to_packets(&first, &routing_header, 1);
+        packets.extend(NaiveAssembler::disassemble_to_packets(
+            &second,
+            &routing_header,
+            2,
+        ));
+        // Incomplete third session
+        let mut third = NaiveAssembler::disassemble_to_packets(&[9; 300], &routing_header, 3);
+        third.remove(1);
+        packets.extend(third);
+        // Packets other than fragments are ignored
+        packets.push(Packet {
+            routing_header: routing_header.clone(),
+            session_id: 1,
+            pack_type: PacketType::Ack(Ack { fragment_index: 0 }),
+        });
+        packets.reverse();
+
+        let messages = NaiveAssembler::reassemble_packets(&packets);
+
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[&(1, 1)], Ok(first));
+        assert_eq!(messages[&(1, 2)], Ok(second));
+        assert_eq!(messages[&(1, 3)], Err(AssemblerError::MissingIndex(1)));
+    }
+
+    #[test]
+    fn reassemble_packets_per_source() {
+        let from = |source| SourceRoutingHeader {
+            hop_index: 1,
+            hops: vec![source, 11, 21],
+        };
+        let mut packets = NaiveAssembler::disassemble_to_packets(&[1; 300], &from(1), 7);
+        packets.extend(NaiveAssembler::disassemble_to_packets(
+            &[2; 200],
+            &from(2),
+            7,
+        ));
+        packets.reverse();
+
+        let messages = NaiveAssembler::reassemble_packets(&packets);
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[&(1, 7)], Ok(vec![1; 300]));
+        assert_eq!(messages[&(2, 7)], Ok(vec![2; 200]));
+    }
+}
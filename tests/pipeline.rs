@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod tests {
+
+    use std::fmt::Debug;
+
+    use assembler::compression::Compression;
+    use assembler::envelope::{EnvelopeError, MessageKind, Tagged};
+    use assembler::pipeline::{Checksum, Envelope, PayloadTransform, Pipeline};
+    use assembler::stateful_assembler::StatefulAssembler;
+    use assembler::AssemblerError;
+    use messages::ChatRequest;
+    use messages::ChatResponse;
+    use messages::DroneSend;
+    use messages::MediaRequest;
+    use messages::MediaResponse;
+    use messages::TextRequest;
+    use messages::TextResponse;
+
+    /// Sends a message through a pipeline with every built-in transform.
+    fn round_trip<T: DroneSend + Tagged + PartialEq + Debug>(message: T) {
+        let mut pipeline = Pipeline::new()
+            .then(Envelope(T::KIND))
+            .then(Compression)
+            .then(Checksum);
+
+        let fragments = pipeline
+            .fragmentize(message.stringify().as_bytes())
+            .unwrap();
+        let bytes = pipeline.assemble(&fragments).unwrap();
+        let deserialized = T::from_string(String::from_utf8(bytes).unwrap()).unwrap();
+
+        assert_eq!(message, deserialized);
+    }
+
+    #[test]
+    fn round_trip_text_messages() {
+        round_trip(TextRequest::Text(74));
+        round_trip(TextRequest::TextList);
+        round_trip(TextResponse::Text("This as a test string.".to_string()));
+        round_trip(TextResponse::Text("Hello, World".repeat(1000)));
+        round_trip(TextResponse::TextList(vec![35, 987, 55, 68, 77, 33, 56, 7]));
+        round_trip(TextResponse::NotFound);
+    }
+
+    #[test]
+    fn round_trip_media_messages() {
+        round_trip(MediaRequest::MediaList);
+        round_trip(MediaRequest::Media(57));
+        round_trip(MediaResponse::MediaList(vec![56, 87, 97, 66]));
+        round_trip(MediaResponse::Media(vec![
+            4, 7, 9, 6, 4, 6, 6, 6, 6, 7, 7, 8,
+        ]));
+        round_trip(MediaResponse::Media(vec![4; 24000]));
+    }
+
+    #[test]
+    fn round_trip_chat_messages() {
+        round_trip(ChatRequest::ClientList);
+        round_trip(ChatRequest::Register(8));
+        round_trip(ChatRequest::SendMessage {
+            from: 8,
+            to: 3,
+            message: String::from("Hello this is a test"),
+        });
+        round_trip(ChatResponse::ClientList(vec![87, 7, 55, 43, 4, 8]));
+        round_trip(ChatResponse::MessageFrom {
+            from: 8,
+            message: vec![16; 204],
+        });
+        round_trip(ChatResponse::MessageSent);
+    }
+
+    /// Transform recording the order in which it is applied.
+    struct Tag(u8);
+
+    impl PayloadTransform for Tag {
+        fn encode(&mut self, payload: &[u8]) -> Result<Vec<u8>, AssemblerError> {
+            let mut encoded = payload.to_vec();
+            encoded.push(self.0);
+            Ok(encoded)
+        }
+
+        fn decode(&mut self, payload: &[u8]) -> Result<Vec<u8>, AssemblerError> {
+            match payload.split_last() {
+                Some((tag, payload)) if *tag == self.0 => Ok(payload.to_vec()),
+                _ => Err(AssemblerError::Deserialization(format!(
+                    "missing tag {}",
+                    self.0
+                ))),
+            }
+        }
+    }
+
+    #[test]
+    fn apply_transforms_in_order() {
+        let mut pipeline = Pipeline::new().then(Tag(1)).then(Tag(2));
+
+        let fragments = pipeline.fragmentize(&[0; 3]).unwrap();
+        assert_eq!(fragments[0].length, 5);
+        assert_eq!(fragments[0].data[..5], [0, 0, 0, 1, 2]);
+        assert_eq!(pipeline.assemble(&fragments), Ok(vec![0; 3]));
+    }
+
+    #[test]
+    fn reject_corrupted_or_foreign_payloads() {
+        let mut sender = Pipeline::new().then(Envelope(MessageKind::TextRequest));
+        let mut receiver = Pipeline::new().then(Envelope(MessageKind::ChatRequest));
+        let fragments = sender.fragmentize(b"{}").unwrap();
+
+        assert_eq!(
+            receiver.assemble(&fragments),
+            Err(AssemblerError::Envelope(EnvelopeError::UnexpectedKind {
+                expected: MessageKind::ChatRequest,
+                found: MessageKind::TextRequest
+            }))
+        );
+
+        let mut pipeline = Pipeline::new().then(Checksum);
+        let mut fragments = pipeline.fragmentize(&[1; 300]).unwrap();
+        fragments[1].data[7] = 0;
+        assert!(matches!(
+            pipeline.assemble(&fragments),
+            Err(AssemblerError::ChecksumMismatch { .. })
+        ));
+    }
+}
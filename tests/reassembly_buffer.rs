@@ -0,0 +1,131 @@
+#[cfg(test)]
+mod tests {
+
+    use assembler::config::AssemblerConfig;
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::reassembly_buffer::ReassemblyBuffer;
+    use assembler::{Assembler, AssemblerError};
+
+    #[test]
+    fn reassemble_out_of_order_fragments() {
+        let bytes = "Hello, World".repeat(100).into_bytes();
+        let mut fragments = NaiveAssembler::disassemble(&bytes);
+        fragments.reverse();
+
+        let mut buffer = ReassemblyBuffer::new();
+        let (last, rest) = fragments.split_last().unwrap();
+        for fragment in rest {
+            assert_eq!(buffer.insert(1, 7, fragment), Ok(None));
+        }
+        assert!(buffer.contains(1, 7));
+
+        // The last missing fragment completes the message
+        assert_eq!(buffer.insert(1, 7, last), Ok(Some(bytes)));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn reassemble_interleaved_sessions() {
+        let first = "First message".repeat(30).into_bytes();
+        let second = "Second message".repeat(20).into_bytes();
+        let first_fragments = NaiveAssembler::disassemble(&first);
+        let second_fragments = NaiveAssembler::disassemble(&second);
+
+        let mut buffer = ReassemblyBuffer::new();
+        let mut completed = Vec::new();
+        // Same session id from two different sources must not be mixed up
+        for (a, b) in first_fragments.iter().zip(second_fragments.iter()) {
+            completed.extend(buffer.insert(1, 0, a).unwrap());
+            completed.extend(buffer.insert(2, 0, b).unwrap());
+        }
+        for fragment in first_fragments.iter().skip(second_fragments.len()) {
+            completed.extend(buffer.insert(1, 0, fragment).unwrap());
+        }
+
+        assert_eq!(completed, vec![second, first]);
+        assert_eq!(buffer.len(), 0);
+    }
+
+    #[test]
+    fn reject_duplicate_fragment() {
+        let fragments = NaiveAssembler::disassemble(&[3; 300]);
+
+        let mut buffer = ReassemblyBuffer::new();
+        assert_eq!(buffer.insert(1, 0, &fragments[0]), Ok(None));
+        assert_eq!(
+            buffer.insert(1, 0, &fragments[0]),
+            Err(AssemblerError::DuplicateIndex(0))
+        );
+        // The message is still being reassembled
+        assert_eq!(buffer.insert(1, 0, &fragments[1]), Ok(None));
+        assert_eq!(buffer.insert(1, 0, &fragments[2]), Ok(Some(vec![3; 300])));
+    }
+
+    #[test]
+    fn reassemble_empty_payload_in_buffer() {
+        let fragments = NaiveAssembler::disassemble(&[]);
+
+        let mut buffer = ReassemblyBuffer::new();
+        assert_eq!(buffer.insert(1, 0, &fragments[0]), Ok(Some(vec![])));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn return_completed_message_once() {
+        let single = NaiveAssembler::disassemble(&[4; 10]);
+        let fragments = NaiveAssembler::disassemble(&[3; 300]);
+
+        let mut buffer = ReassemblyBuffer::new();
+        assert_eq!(buffer.insert(1, 0, &single[0]), Ok(Some(vec![4; 10])));
+        for fragment in &fragments {
+            buffer.insert(1, 1, fragment).unwrap();
+        }
+
+        // Fragments retransmitted after completion start no new message
+        assert_eq!(buffer.insert(1, 0, &single[0]), Ok(None));
+        assert_eq!(buffer.insert(1, 1, &fragments[2]), Ok(None));
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.buffered_bytes(), 0);
+    }
+
+    #[test]
+    fn forget_oldest_completed_message() {
+        let config = AssemblerConfig {
+            max_completed_per_source: 2,
+            ..AssemblerConfig::default()
+        };
+        let fragments = NaiveAssembler::disassemble(&[3; 10]);
+
+        let mut buffer = ReassemblyBuffer::with_config(config);
+        for session_id in 0..3 {
+            assert_eq!(
+                buffer.insert(1, session_id, &fragments[0]),
+                Ok(Some(vec![3; 10]))
+            );
+        }
+        // Other sources are remembered separately
+        buffer.insert(2, 0, &fragments[0]).unwrap();
+
+        assert_eq!(buffer.insert(1, 2, &fragments[0]), Ok(None));
+        assert_eq!(buffer.insert(1, 1, &fragments[0]), Ok(None));
+        assert_eq!(buffer.insert(2, 0, &fragments[0]), Ok(None));
+        // The oldest message of source 1 was forgotten
+        assert_eq!(buffer.insert(1, 0, &fragments[0]), Ok(Some(vec![3; 10])));
+    }
+
+    #[test]
+    fn reject_malformed_first_fragment() {
+        let mut fragments = NaiveAssembler::disassemble(&[3; 300]);
+        fragments[0].fragment_index = 5;
+
+        let mut buffer = ReassemblyBuffer::new();
+        assert_eq!(
+            buffer.insert(1, 0, &fragments[0]),
+            Err(AssemblerError::IndexOutOfRange {
+                fragment_index: 5,
+                total_n_fragments: 3
+            })
+        );
+        assert!(buffer.is_empty());
+    }
+}
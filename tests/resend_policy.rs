@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests {
+
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::resend_policy::{
+        DefaultResendPolicy, NackCounters, ResendDecision, ResendPolicy,
+    };
+    use assembler::send_buffer::SendBuffer;
+    use assembler::Assembler;
+    use wg_2024::packet::{Ack, Nack, NackType};
+
+    fn nack(fragment_index: u64, nack_type: NackType) -> Nack {
+        Nack {
+            fragment_index,
+            nack_type,
+        }
+    }
+
+    #[test]
+    fn classify_nack_types() {
+        let mut buffer = SendBuffer::new();
+        buffer.insert(3, NaiveAssembler::disassemble(&[1; 300]));
+
+        let (decision, fragment) = buffer.decide_nack(3, &nack(1, NackType::Dropped)).unwrap();
+        assert_eq!(decision, ResendDecision::Resend);
+        assert_eq!(fragment.fragment_index, 1);
+        assert_eq!(
+            buffer
+                .decide_nack(3, &nack(0, NackType::ErrorInRouting(7)))
+                .unwrap()
+                .0,
+            ResendDecision::Reroute {
+                faulty_node: Some(7)
+            }
+        );
+        assert_eq!(
+            buffer
+                .decide_nack(3, &nack(2, NackType::DestinationIsDrone))
+                .unwrap()
+                .0,
+            ResendDecision::Reroute { faulty_node: None }
+        );
+        assert_eq!(
+            buffer
+                .decide_nack(3, &nack(2, NackType::UnexpectedRecipient(5)))
+                .unwrap()
+                .0,
+            ResendDecision::Reroute { faulty_node: None }
+        );
+        assert_eq!(buffer.session(3).unwrap().nacks(2), 2);
+    }
+
+    #[test]
+    fn give_up_after_too_many_nacks() {
+        let mut buffer = SendBuffer::with_policy(DefaultResendPolicy { max_nacks: 3 });
+        buffer.insert(3, NaiveAssembler::disassemble(&[1; 10]));
+
+        let dropped = nack(0, NackType::Dropped);
+        assert_eq!(
+            buffer.decide_nack(3, &dropped).unwrap().0,
+            ResendDecision::Resend
+        );
+        assert_eq!(
+            buffer.decide_nack(3, &dropped).unwrap().0,
+            ResendDecision::Resend
+        );
+        assert_eq!(
+            buffer.decide_nack(3, &dropped).unwrap().0,
+            ResendDecision::GiveUp
+        );
+    }
+
+    #[test]
+    fn count_nacks_per_type() {
+        let mut buffer = SendBuffer::new();
+        buffer.insert(3, NaiveAssembler::disassemble(&[1; 300]));
+        buffer.handle_ack(3, &Ack { fragment_index: 2 });
+
+        buffer.decide_nack(3, &nack(0, NackType::Dropped));
+        buffer.decide_nack(3, &nack(1, NackType::Dropped));
+        buffer.decide_nack(3, &nack(1, NackType::ErrorInRouting(4)));
+        // Nacks of acknowledged fragments or unknown sessions are still counted
+        assert!(buffer.decide_nack(3, &nack(2, NackType::Dropped)).is_none());
+        assert!(buffer
+            .decide_nack(8, &nack(0, NackType::DestinationIsDrone))
+            .is_none());
+
+        assert_eq!(
+            *buffer.nack_counters(),
+            NackCounters {
+                dropped: 3,
+                error_in_routing: 1,
+                destination_is_drone: 1,
+                unexpected_recipient: 0
+            }
+        );
+        assert_eq!(buffer.nack_counters().total(), 5);
+    }
+
+    /// Policy never resending on the same route.
+    struct AlwaysReroute;
+
+    impl ResendPolicy for AlwaysReroute {
+        fn decide(&mut self, _nack_type: &NackType, _nacks: u32) -> ResendDecision {
+            ResendDecision::Reroute { faulty_node: None }
+        }
+    }
+
+    #[test]
+    fn plug_custom_policy() {
+        let mut buffer = SendBuffer::with_policy(AlwaysReroute);
+        buffer.insert(1, NaiveAssembler::disassemble(&[1; 10]));
+
+        assert_eq!(
+            buffer
+                .decide_nack(1, &nack(0, NackType::Dropped))
+                .unwrap()
+                .0,
+            ResendDecision::Reroute { faulty_node: None }
+        );
+    }
+}
@@ -0,0 +1,198 @@
+#[cfg(test)]
+mod tests {
+
+    use std::time::Duration;
+
+    use assembler::clock::{Clock, ManualClock};
+    use assembler::config::SendConfig;
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::resend_policy::DefaultResendPolicy;
+    use assembler::rtt::RttEstimator;
+    use assembler::send_buffer::{SendBuffer, TimerEvent};
+    use assembler::Assembler;
+    use wg_2024::packet::{Ack, Nack, NackType};
+
+    fn config() -> SendConfig {
+        SendConfig {
+            initial_rto: Duration::from_secs(1),
+            min_rto: Duration::from_millis(100),
+            max_rto: Duration::from_secs(10),
+            max_retries: 2,
+        }
+    }
+
+    fn buffer(clock: &ManualClock) -> SendBuffer<DefaultResendPolicy, ManualClock> {
+        SendBuffer::with_clock(DefaultResendPolicy::default(), config(), clock.clone())
+    }
+
+    fn retransmitted(events: &[TimerEvent]) -> Vec<(u64, u64)> {
+        events
+            .iter()
+            .map(|event| match event {
+                TimerEvent::Retransmit {
+                    session_id,
+                    fragment,
+                } => (*session_id, fragment.fragment_index),
+                TimerEvent::GiveUp { .. } => panic!("expected a Retransmit event"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn estimate_rtt() {
+        let mut rtt = RttEstimator::new(&config());
+        assert_eq!(rtt.rto(), Duration::from_secs(1));
+        assert_eq!(rtt.smoothed_rtt(), None);
+
+        rtt.sample(Duration::from_millis(200));
+        assert_eq!(rtt.smoothed_rtt(), Some(Duration::from_millis(200)));
+        assert_eq!(rtt.rtt_variance(), Duration::from_millis(100));
+        assert_eq!(rtt.rto(), Duration::from_millis(600));
+
+        rtt.sample(Duration::from_millis(600));
+        assert_eq!(rtt.smoothed_rtt(), Some(Duration::from_millis(250)));
+        assert_eq!(rtt.rtt_variance(), Duration::from_millis(175));
+        assert_eq!(rtt.rto(), Duration::from_millis(950));
+    }
+
+    #[test]
+    fn clamp_and_back_off_timeout() {
+        let mut rtt = RttEstimator::new(&config());
+        rtt.sample(Duration::ZERO);
+        assert_eq!(rtt.rto(), Duration::from_millis(100));
+
+        assert_eq!(rtt.backoff(0), Duration::from_millis(100));
+        assert_eq!(rtt.backoff(3), Duration::from_millis(800));
+        assert_eq!(rtt.backoff(10), Duration::from_secs(10));
+        assert_eq!(rtt.backoff(u32::MAX), Duration::from_secs(10));
+    }
+
+    #[test]
+    #[should_panic(expected = "greater than max_rto")]
+    fn reject_inverted_rto_bounds() {
+        let _ = RttEstimator::new(&SendConfig {
+            min_rto: Duration::from_secs(2),
+            max_rto: Duration::from_secs(1),
+            ..config()
+        });
+    }
+
+    #[test]
+    fn measure_rtt_from_acks() {
+        let clock = ManualClock::new();
+        let mut buffer = buffer(&clock);
+        let sent_at = clock.now();
+        buffer.insert(1, NaiveAssembler::disassemble(&[1; 300]));
+
+        clock.advance(Duration::from_millis(200));
+        buffer.handle_ack(1, &Ack { fragment_index: 0 });
+        assert_eq!(
+            buffer.rtt().smoothed_rtt(),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(buffer.rtt().rto(), Duration::from_millis(600));
+        assert_eq!(
+            buffer.next_timeout(),
+            Some(sent_at + Duration::from_millis(600))
+        );
+    }
+
+    #[test]
+    fn retransmit_on_timeout_with_backoff() {
+        let clock = ManualClock::new();
+        let mut buffer = buffer(&clock);
+        buffer.insert(1, NaiveAssembler::disassemble(&[1; 300]));
+        buffer.insert(2, NaiveAssembler::disassemble(&[1; 10]));
+
+        clock.advance(Duration::from_millis(999));
+        assert!(buffer.poll_timeouts().is_empty());
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(
+            retransmitted(&buffer.poll_timeouts()),
+            [(1, 0), (1, 1), (1, 2), (2, 0)]
+        );
+        assert_eq!(buffer.session(1).unwrap().retries(0), 1);
+        assert!(buffer.poll_timeouts().is_empty());
+        buffer.handle_ack(1, &Ack { fragment_index: 1 });
+
+        // The timeout doubles after every retransmission
+        clock.advance(Duration::from_millis(1999));
+        assert!(buffer.poll_timeouts().is_empty());
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(
+            retransmitted(&buffer.poll_timeouts()),
+            [(1, 0), (1, 2), (2, 0)]
+        );
+    }
+
+    #[test]
+    fn give_up_after_max_retries() {
+        let clock = ManualClock::new();
+        let mut buffer = buffer(&clock);
+        buffer.insert(4, NaiveAssembler::disassemble(&[1; 10]));
+
+        for _ in 0..2 {
+            clock.advance(Duration::from_secs(10));
+            assert_eq!(retransmitted(&buffer.poll_timeouts()), [(4, 0)]);
+        }
+        clock.advance(Duration::from_secs(10));
+        let events = buffer.poll_timeouts();
+        assert!(matches!(
+            events[..],
+            [TimerEvent::GiveUp {
+                session_id: 4,
+                fragment_index: 0
+            }]
+        ));
+
+        // The session is kept, but its fragment is no longer retransmitted
+        clock.advance(Duration::from_secs(10));
+        assert!(buffer.poll_timeouts().is_empty());
+        assert_eq!(buffer.next_timeout(), None);
+        assert_eq!(buffer.len(), 1);
+    }
+
+    #[test]
+    fn ignore_rtt_of_retransmitted_fragments() {
+        let clock = ManualClock::new();
+        let mut buffer = buffer(&clock);
+        buffer.insert(1, NaiveAssembler::disassemble(&[1; 300]));
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(buffer.poll_timeouts().len(), 3);
+        clock.advance(Duration::from_millis(50));
+        buffer.handle_ack(1, &Ack { fragment_index: 0 });
+
+        // A fragment resent after a Nack is ambiguous as well
+        buffer.decide_nack(
+            1,
+            &Nack {
+                fragment_index: 1,
+                nack_type: NackType::Dropped,
+            },
+        );
+        buffer.handle_ack(1, &Ack { fragment_index: 1 });
+        assert_eq!(buffer.rtt().smoothed_rtt(), None);
+        assert_eq!(buffer.rtt().rto(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn start_timer_when_queued_fragment_is_sent() {
+        let clock = ManualClock::new();
+        let mut buffer = buffer(&clock);
+        buffer.insert_queued(1, NaiveAssembler::disassemble(&[1; 300]));
+
+        clock.advance(Duration::from_secs(5));
+        assert!(buffer.poll_timeouts().is_empty());
+        assert_eq!(buffer.next_timeout(), None);
+
+        assert!(buffer.mark_sent(1, 2));
+        assert!(!buffer.mark_sent(1, 3));
+        clock.advance(Duration::from_millis(300));
+        buffer.handle_ack(1, &Ack { fragment_index: 2 });
+        assert_eq!(
+            buffer.rtt().smoothed_rtt(),
+            Some(Duration::from_millis(300))
+        );
+    }
+}
@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests {
+
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::send_buffer::{AckOutcome, SendBuffer};
+    use assembler::Assembler;
+    use wg_2024::packet::{Ack, Nack, NackType};
+
+    #[test]
+    fn release_session_once_fully_acknowledged() {
+        let fragments = NaiveAssembler::disassemble(&[1; 300]);
+
+        let mut buffer = SendBuffer::new();
+        buffer.insert(4, fragments);
+
+        assert_eq!(
+            buffer.handle_ack(4, &Ack { fragment_index: 1 }),
+            AckOutcome::Pending
+        );
+        // Acknowledging the same fragment twice changes nothing
+        assert_eq!(
+            buffer.handle_ack(4, &Ack { fragment_index: 1 }),
+            AckOutcome::Ignored
+        );
+        assert_eq!(
+            buffer.handle_ack(4, &Ack { fragment_index: 0 }),
+            AckOutcome::Pending
+        );
+        assert_eq!(buffer.session(4).unwrap().unacknowledged().count(), 1);
+        assert_eq!(
+            buffer.handle_ack(4, &Ack { fragment_index: 2 }),
+            AckOutcome::Completed
+        );
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn resend_exact_fragment_on_nack() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let fragments = NaiveAssembler::disassemble(&bytes);
+
+        let mut buffer = SendBuffer::new();
+        buffer.insert(9, fragments.clone());
+
+        let dropped = Nack {
+            fragment_index: 1,
+            nack_type: NackType::Dropped,
+        };
+        let resend = buffer.handle_nack(9, &dropped).unwrap();
+        assert_eq!(resend.fragment_index, 1);
+        assert_eq!(resend.data, fragments[1].data);
+
+        let routing_error = Nack {
+            fragment_index: 0,
+            nack_type: NackType::ErrorInRouting(3),
+        };
+        assert_eq!(
+            buffer
+                .handle_nack(9, &routing_error)
+                .unwrap()
+                .fragment_index,
+            0
+        );
+    }
+
+    #[test]
+    fn ignore_nack_for_unknown_or_acknowledged_fragment() {
+        let mut buffer = SendBuffer::new();
+        buffer.insert(9, NaiveAssembler::disassemble(&[1; 300]));
+        buffer.handle_ack(9, &Ack { fragment_index: 0 });
+
+        let nack = |fragment_index| Nack {
+            fragment_index,
+            nack_type: NackType::Dropped,
+        };
+        assert!(buffer.handle_nack(9, &nack(0)).is_none());
+        assert!(buffer.handle_nack(9, &nack(3)).is_none());
+        assert!(buffer.handle_nack(10, &nack(1)).is_none());
+        assert!(buffer.handle_nack(9, &nack(1)).is_some());
+    }
+}
@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+
+    use assembler::config::{AssemblerConfig, DuplicatePolicy};
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::stateful_assembler::{AssemblerStats, ConfiguredAssembler, StatefulAssembler};
+    use assembler::AssemblerError;
+
+    fn round_trip(assembler: &mut dyn StatefulAssembler, bytes: &[u8]) -> Vec<u8> {
+        let fragments = assembler.fragmentize(bytes).unwrap();
+        assembler.assemble(&fragments).unwrap()
+    }
+
+    #[test]
+    fn swap_assemblers_at_runtime() {
+        let bytes = "Hello, World".repeat(100).into_bytes();
+        let checked = AssemblerConfig {
+            verify_checksum: true,
+            ..AssemblerConfig::default()
+        };
+        let mut assemblers: Vec<Box<dyn StatefulAssembler>> = vec![
+            Box::new(NaiveAssembler::default()),
+            Box::new(ConfiguredAssembler::default()),
+            Box::new(ConfiguredAssembler::new(checked)),
+        ];
+
+        for assembler in &mut assemblers {
+            assert_eq!(round_trip(assembler.as_mut(), &bytes), bytes);
+            assert_eq!(round_trip(assembler.as_mut(), &[]), Vec::<u8>::new());
+        }
+    }
+
+    #[test]
+    fn apply_configuration() {
+        let config = AssemblerConfig {
+            duplicate_policy: DuplicatePolicy::Ignore,
+            verify_checksum: true,
+            ..AssemblerConfig::default()
+        };
+        let mut assembler = ConfiguredAssembler::new(config);
+
+        let mut fragments = assembler.fragmentize(&[1; 300]).unwrap();
+        // The checksum trailer is carried by the fragments
+        assert_eq!(fragments[2].length, 48);
+        fragments.push(fragments[0].clone());
+        assert_eq!(assembler.assemble(&fragments), Ok(vec![1; 300]));
+
+        fragments[1].data[0] = 2;
+        assert!(matches!(
+            assembler.assemble(&fragments),
+            Err(AssemblerError::ChecksumMismatch { .. })
+        ));
+        assert_eq!(
+            assembler.stats(),
+            AssemblerStats {
+                messages_disassembled: 1,
+                messages_reassembled: 1,
+                errors: 1
+            }
+        );
+    }
+
+    #[test]
+    fn enforce_limits_in_both_directions() {
+        let config = AssemblerConfig {
+            max_message_size: 256,
+            ..AssemblerConfig::default()
+        };
+        let mut assembler = ConfiguredAssembler::new(config);
+
+        assert_eq!(assembler.fragmentize(&[1; 256]).unwrap().len(), 2);
+        assert!(matches!(
+            assembler.fragmentize(&[1; 257]),
+            Err(AssemblerError::LimitExceeded(_))
+        ));
+        let fragments = NaiveAssembler::default().fragmentize(&[1; 300]).unwrap();
+        assert!(matches!(
+            assembler.assemble(&fragments),
+            Err(AssemblerError::LimitExceeded(_))
+        ));
+    }
+}
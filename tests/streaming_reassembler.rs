@@ -0,0 +1,132 @@
+#[cfg(test)]
+mod tests {
+
+    use std::io::{self, ErrorKind, Write};
+
+    use assembler::config::{AssemblerConfig, DuplicatePolicy};
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::streaming_reassembler::StreamingReassembler;
+    use assembler::{Assembler, AssemblerError};
+
+    #[test]
+    fn write_contiguous_prefix() {
+        let bytes: Vec<u8> = (0..1000).map(|i| (i % 251) as u8).collect();
+        let fragments = NaiveAssembler::disassemble(&bytes);
+
+        let mut reassembler = StreamingReassembler::new(Vec::new());
+        assert_eq!(reassembler.insert(&fragments[2]), Ok(false));
+        assert_eq!(reassembler.insert(&fragments[1]), Ok(false));
+        assert_eq!(reassembler.bytes_written(), 0);
+        assert_eq!(reassembler.buffered_fragments(), 2);
+
+        // The first fragment releases the two buffered ones
+        assert_eq!(reassembler.insert(&fragments[0]), Ok(false));
+        assert_eq!(reassembler.written_fragments(), 3);
+        assert_eq!(reassembler.buffered_fragments(), 0);
+        assert_eq!(reassembler.get_ref().as_slice(), &bytes[..384]);
+
+        for fragment in fragments[3..7].iter().rev() {
+            assert_eq!(reassembler.insert(fragment), Ok(false));
+        }
+        assert_eq!(
+            reassembler.missing().unwrap().indexes().collect::<Vec<_>>(),
+            [7]
+        );
+        assert_eq!(reassembler.insert(&fragments[7]), Ok(true));
+        assert!(reassembler.is_complete());
+        assert_eq!(reassembler.into_inner(), bytes);
+    }
+
+    #[test]
+    fn stream_single_empty_fragment() {
+        let mut reassembler = StreamingReassembler::new(Vec::new());
+
+        assert_eq!(
+            reassembler.insert(&NaiveAssembler::disassemble(&[])[0]),
+            Ok(true)
+        );
+        assert!(reassembler.into_inner().is_empty());
+    }
+
+    #[test]
+    fn reject_fragments_of_another_message() {
+        let fragments = NaiveAssembler::disassemble(&[1; 300]);
+        let other = NaiveAssembler::disassemble(&[1; 1000]);
+
+        let mut reassembler = StreamingReassembler::new(Vec::new());
+        assert_eq!(reassembler.insert(&fragments[1]), Ok(false));
+        assert_eq!(
+            reassembler.insert(&other[0]),
+            Err(AssemblerError::InconsistentTotal {
+                fragment_index: 0,
+                expected: 3,
+                found: 8
+            })
+        );
+        assert_eq!(reassembler.insert(&fragments[0]), Ok(false));
+        assert_eq!(
+            reassembler.insert(&fragments[0]),
+            Err(AssemblerError::DuplicateIndex(0))
+        );
+        assert_eq!(reassembler.insert(&fragments[2]), Ok(true));
+    }
+
+    #[test]
+    fn ignore_retransmitted_fragments() {
+        let fragments = NaiveAssembler::disassemble(&[1; 300]);
+        let config = AssemblerConfig {
+            duplicate_policy: DuplicatePolicy::Ignore,
+            ..AssemblerConfig::default()
+        };
+
+        let mut reassembler = StreamingReassembler::with_config(Vec::new(), config);
+        assert_eq!(reassembler.insert(&fragments[2]), Ok(false));
+        assert_eq!(reassembler.insert(&fragments[2]), Ok(false));
+        assert_eq!(reassembler.insert(&fragments[0]), Ok(false));
+        assert_eq!(reassembler.insert(&fragments[0]), Ok(false));
+        assert_eq!(reassembler.insert(&fragments[1]), Ok(true));
+        assert_eq!(reassembler.insert(&fragments[1]), Ok(true));
+        assert_eq!(reassembler.into_inner(), vec![1; 300]);
+    }
+
+    #[test]
+    fn bound_out_of_order_tail() {
+        let fragments = NaiveAssembler::disassemble(&[1; 1000]);
+        let config = AssemblerConfig {
+            max_buffered_bytes: 256,
+            ..AssemblerConfig::default()
+        };
+
+        let mut reassembler = StreamingReassembler::with_config(Vec::new(), config);
+        assert_eq!(reassembler.insert(&fragments[1]), Ok(false));
+        assert_eq!(reassembler.insert(&fragments[2]), Ok(false));
+        assert!(matches!(
+            reassembler.insert(&fragments[3]),
+            Err(AssemblerError::LimitExceeded(_))
+        ));
+    }
+
+    struct FailingSink;
+
+    impl Write for FailingSink {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::from(ErrorKind::StorageFull))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn report_sink_errors() {
+        let fragments = NaiveAssembler::disassemble(&[1; 300]);
+
+        let mut reassembler = StreamingReassembler::new(FailingSink);
+        assert_eq!(reassembler.insert(&fragments[1]), Ok(false));
+        assert_eq!(
+            reassembler.insert(&fragments[0]),
+            Err(AssemblerError::Io(ErrorKind::StorageFull))
+        );
+    }
+}
@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::{Assembler, AssemblerError};
+
+    #[test]
+    fn try_reassemble_round_trip() {
+        let bytes = "Hello, World".repeat(100).into_bytes();
+        // Create fragments
+        let fragments = NaiveAssembler::try_disassemble(&bytes).unwrap();
+        // Reassemble fragments in reverse order
+        let mut reversed = fragments.clone();
+        reversed.reverse();
+
+        assert_eq!(NaiveAssembler::try_reassemble(&reversed), Ok(bytes));
+    }
+
+    #[test]
+    fn try_reassemble_empty_input() {
+        assert_eq!(
+            NaiveAssembler::try_reassemble(&[]),
+            Err(AssemblerError::Empty)
+        );
+    }
+
+    #[test]
+    fn try_reassemble_duplicate_index() {
+        let mut fragments = NaiveAssembler::disassemble(&[7; 300]);
+        // Replace the last fragment with a copy of the first one
+        fragments[2] = fragments[0].clone();
+
+        assert_eq!(
+            NaiveAssembler::try_reassemble(&fragments),
+            Err(AssemblerError::DuplicateIndex(0))
+        );
+    }
+
+    #[test]
+    fn try_reassemble_missing_index() {
+        let mut fragments = NaiveAssembler::disassemble(&[7; 300]);
+        fragments.remove(1);
+
+        assert_eq!(
+            NaiveAssembler::try_reassemble(&fragments),
+            Err(AssemblerError::MissingIndex(1))
+        );
+    }
+
+    #[test]
+    fn try_reassemble_index_out_of_range() {
+        let mut fragments = NaiveAssembler::disassemble(&[7; 300]);
+        fragments[1].fragment_index = 3;
+
+        assert_eq!(
+            NaiveAssembler::try_reassemble(&fragments),
+            Err(AssemblerError::IndexOutOfRange {
+                fragment_index: 3,
+                total_n_fragments: 3
+            })
+        );
+    }
+
+    #[test]
+    fn try_reassemble_inconsistent_total() {
+        let mut fragments = NaiveAssembler::disassemble(&[7; 300]);
+        fragments[2].total_n_fragments = 4;
+
+        assert_eq!(
+            NaiveAssembler::try_reassemble(&fragments),
+            Err(AssemblerError::InconsistentTotal {
+                fragment_index: 2,
+                expected: 3,
+                found: 4
+            })
+        );
+    }
+
+    #[test]
+    fn try_reassemble_invalid_length() {
+        let mut fragments = NaiveAssembler::disassemble(&[7; 300]);
+        fragments[2].length = 200;
+
+        assert_eq!(
+            NaiveAssembler::try_reassemble(&fragments),
+            Err(AssemblerError::InvalidLength {
+                fragment_index: 2,
+                length: 200
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Fragments cannot be reassembled")]
+    fn reassemble_panics_on_duplicate_index() {
+        let fragments = NaiveAssembler::disassemble(&[7; 100]);
+        let fragments = vec![fragments[0].clone(), fragments[0].clone()];
+        NaiveAssembler::reassemble(&fragments);
+    }
+}
@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod tests {
+
+    use assembler::config::AssemblerConfig;
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::reassembly_buffer::ReassemblyBuffer;
+    use assembler::validation::validate_fragments;
+    use assembler::{Assembler, AssemblerError};
+
+    #[test]
+    fn accept_well_formed_fragments() {
+        let bytes = "Hello, World".repeat(100).into_bytes();
+        let fragments = NaiveAssembler::disassemble(&bytes);
+
+        assert_eq!(validate_fragments(&fragments), Ok(()));
+        assert_eq!(NaiveAssembler::try_reassemble_strict(&fragments), Ok(bytes));
+        // A single empty fragment is a valid empty payload
+        assert_eq!(
+            validate_fragments(&NaiveAssembler::disassemble(&[])),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn reject_short_middle_fragment() {
+        let mut fragments = NaiveAssembler::disassemble(&[1; 300]);
+        fragments[1].length = 100;
+        fragments[1].data[100..].fill(0);
+
+        // Lenient reassembly silently fills the hole with zeros
+        assert!(NaiveAssembler::try_reassemble(&fragments).is_ok());
+        assert_eq!(
+            NaiveAssembler::try_reassemble_strict(&fragments),
+            Err(AssemblerError::ShortFragment {
+                fragment_index: 1,
+                length: 100
+            })
+        );
+    }
+
+    #[test]
+    fn reject_empty_last_fragment() {
+        let mut fragments = NaiveAssembler::disassemble(&[1; 256]);
+        fragments.push(fragments[0].clone());
+        for fragment in &mut fragments {
+            fragment.total_n_fragments = 3;
+        }
+        fragments[2].fragment_index = 2;
+        fragments[2].length = 0;
+        fragments[2].data = [0; 128];
+
+        assert_eq!(
+            validate_fragments(&fragments),
+            Err(AssemblerError::ShortFragment {
+                fragment_index: 2,
+                length: 0
+            })
+        );
+    }
+
+    #[test]
+    fn reject_non_zero_padding() {
+        let mut fragments = NaiveAssembler::disassemble(&[1; 300]);
+        fragments[2].data[100] = 1;
+
+        assert_eq!(
+            validate_fragments(&fragments),
+            Err(AssemblerError::NonZeroPadding { fragment_index: 2 })
+        );
+    }
+
+    #[test]
+    fn reject_structural_errors() {
+        let mut fragments = NaiveAssembler::disassemble(&[1; 300]);
+        fragments[0].length = 129;
+
+        assert_eq!(
+            validate_fragments(&fragments),
+            Err(AssemblerError::InvalidLength {
+                fragment_index: 0,
+                length: 129
+            })
+        );
+        assert_eq!(validate_fragments(&[]), Err(AssemblerError::Empty));
+    }
+
+    #[test]
+    fn strict_reassembly_buffer() {
+        let config = AssemblerConfig {
+            strict: true,
+            ..AssemblerConfig::default()
+        };
+        let mut buffer = ReassemblyBuffer::with_config(config);
+        let mut fragments = NaiveAssembler::disassemble(&[1; 300]);
+        fragments[2].data[127] = 9;
+
+        assert_eq!(buffer.insert(1, 0, &fragments[0]), Ok(None));
+        assert_eq!(
+            buffer.insert(1, 0, &fragments[2]),
+            Err(AssemblerError::NonZeroPadding { fragment_index: 2 })
+        );
+        assert_eq!(buffer.missing(1, 0).unwrap().count(), 2);
+    }
+}
@@ -0,0 +1,255 @@
+#[cfg(test)]
+mod tests {
+
+    use std::time::Duration;
+
+    use assembler::clock::ManualClock;
+    use assembler::config::{SendConfig, WindowConfig};
+    use assembler::naive_assembler::NaiveAssembler;
+    use assembler::resend_policy::{DefaultResendPolicy, ResendDecision};
+    use assembler::send_buffer::{AckOutcome, SendBuffer};
+    use assembler::windowed_sender::WindowedSender;
+    use assembler::Assembler;
+    use wg_2024::network::SourceRoutingHeader;
+    use wg_2024::packet::{Ack, Fragment, Nack, NackType, Packet, PacketType};
+
+    fn route(hops: Vec<u8>) -> SourceRoutingHeader {
+        SourceRoutingHeader { hop_index: 0, hops }
+    }
+
+    fn fragment(packet: &Packet) -> &Fragment {
+        let PacketType::MsgFragment(fragment) = &packet.pack_type else {
+            panic!("expected a MsgFragment packet");
+        };
+        fragment
+    }
+
+    fn indexes(packets: &[Packet]) -> Vec<u64> {
+        packets
+            .iter()
+            .map(|packet| fragment(packet).fragment_index)
+            .collect()
+    }
+
+    fn dropped(fragment_index: u64) -> Nack {
+        Nack {
+            fragment_index,
+            nack_type: NackType::Dropped,
+        }
+    }
+
+    #[test]
+    fn keep_window_in_flight() {
+        let mut sender = WindowedSender::new();
+        sender.insert(
+            7,
+            NaiveAssembler::disassemble(&[1; 2560]),
+            &route(vec![1, 4, 9]),
+        );
+        assert_eq!(sender.window(7), Some(4));
+
+        let packets = sender.poll_next_packets();
+        assert_eq!(indexes(&packets), [0, 1, 2, 3]);
+        assert_eq!(packets[0].session_id, 7);
+        assert_eq!(packets[0].routing_header.hop_index, 1);
+        assert_eq!(packets[0].routing_header.hops, [1, 4, 9]);
+        assert_eq!(sender.in_flight(7), Some(4));
+        assert!(sender.poll_next_packets().is_empty());
+
+        // Every Ack releases a new fragment
+        assert_eq!(
+            sender.handle_ack(7, &Ack { fragment_index: 1 }),
+            AckOutcome::Pending
+        );
+        assert_eq!(indexes(&sender.poll_next_packets()), [4]);
+    }
+
+    #[test]
+    fn ignore_acks_of_unsent_fragments() {
+        let mut sender = WindowedSender::new();
+        sender.insert(
+            7,
+            NaiveAssembler::disassemble(&[1; 2560]),
+            &route(vec![1, 9]),
+        );
+        sender.poll_next_packets();
+
+        assert_eq!(
+            sender.handle_ack(7, &Ack { fragment_index: 10 }),
+            AckOutcome::Ignored
+        );
+        assert_eq!(sender.in_flight(7), Some(4));
+        assert!(sender.poll_next_packets().is_empty());
+    }
+
+    #[test]
+    fn ignore_nacks_of_unsent_fragments() {
+        let mut sender = WindowedSender::new();
+        sender.insert(
+            7,
+            NaiveAssembler::disassemble(&[1; 2560]),
+            &route(vec![1, 9]),
+        );
+        sender.poll_next_packets();
+
+        assert_eq!(sender.handle_nack(7, &dropped(10)), None);
+        assert_eq!(sender.window(7), Some(4));
+        assert_eq!(sender.in_flight(7), Some(4));
+        assert!(sender.poll_next_packets().is_empty());
+    }
+
+    #[test]
+    fn grow_window_on_acks() {
+        let mut sender = WindowedSender::new();
+        sender.insert(
+            7,
+            NaiveAssembler::disassemble(&[1; 2560]),
+            &route(vec![1, 9]),
+        );
+        sender.poll_next_packets();
+
+        for fragment_index in 0..4 {
+            sender.handle_ack(7, &Ack { fragment_index });
+        }
+        assert_eq!(sender.window(7), Some(5));
+        assert_eq!(indexes(&sender.poll_next_packets()), [4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn shrink_window_on_drops() {
+        let mut sender = WindowedSender::new();
+        sender.insert(
+            7,
+            NaiveAssembler::disassemble(&[1; 2560]),
+            &route(vec![1, 9]),
+        );
+        sender.poll_next_packets();
+
+        assert_eq!(
+            sender.handle_nack(7, &dropped(2)),
+            Some(ResendDecision::Resend)
+        );
+        assert_eq!(sender.window(7), Some(2));
+        // The dropped fragment is resent, but no new one fits into the window
+        assert_eq!(indexes(&sender.poll_next_packets()), [2]);
+
+        // A fragment nacked twice before the next poll is resent once
+        sender.handle_nack(7, &dropped(3));
+        sender.handle_nack(7, &dropped(3));
+        assert_eq!(sender.window(7), Some(1));
+        assert_eq!(indexes(&sender.poll_next_packets()), [3]);
+
+        // The window grows back by one fragment per window of Acks
+        for fragment_index in 0..3 {
+            sender.handle_ack(7, &Ack { fragment_index });
+        }
+        assert_eq!(sender.window(7), Some(3));
+        assert_eq!(indexes(&sender.poll_next_packets()), [4, 5]);
+    }
+
+    #[test]
+    fn resend_along_new_route() {
+        let mut sender = WindowedSender::new();
+        sender.insert(
+            3,
+            NaiveAssembler::disassemble(&[1; 300]),
+            &route(vec![1, 4, 9]),
+        );
+        sender.poll_next_packets();
+
+        let nack = Nack {
+            fragment_index: 1,
+            nack_type: NackType::ErrorInRouting(4),
+        };
+        assert_eq!(
+            sender.handle_nack(3, &nack),
+            Some(ResendDecision::Reroute {
+                faulty_node: Some(4)
+            })
+        );
+        assert_eq!(sender.window(3), Some(4));
+        assert!(sender.set_route(3, &route(vec![1, 5, 9])));
+
+        let packets = sender.poll_next_packets();
+        assert_eq!(indexes(&packets), [1]);
+        assert_eq!(packets[0].routing_header.hops, [1, 5, 9]);
+    }
+
+    #[test]
+    fn deliver_large_message() {
+        let bytes: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let mut sender = WindowedSender::with_config(WindowConfig {
+            initial_window: 2,
+            min_window: 1,
+            max_window: 16,
+        });
+        sender.insert(1, NaiveAssembler::disassemble(&bytes), &route(vec![1, 9]));
+
+        let mut received = Vec::new();
+        loop {
+            let packets = sender.poll_next_packets();
+            assert!(packets.len() <= 16);
+            if packets.is_empty() {
+                break;
+            }
+            for packet in packets {
+                let fragment = fragment(&packet).clone();
+                let ack = Ack {
+                    fragment_index: fragment.fragment_index,
+                };
+                received.push(fragment);
+                sender.handle_ack(1, &ack);
+            }
+        }
+
+        assert!(sender.is_empty());
+        assert_eq!(NaiveAssembler::reassemble(&received), bytes);
+    }
+
+    #[test]
+    fn stop_sending_given_up_session() {
+        let buffer = SendBuffer::with_policy(DefaultResendPolicy { max_nacks: 1 });
+        let mut sender = WindowedSender::with_send_buffer(WindowConfig::default(), buffer);
+        sender.insert(
+            2,
+            NaiveAssembler::disassemble(&[1; 2560]),
+            &route(vec![1, 9]),
+        );
+        sender.poll_next_packets();
+
+        assert_eq!(
+            sender.handle_nack(2, &dropped(0)),
+            Some(ResendDecision::GiveUp)
+        );
+        assert!(sender.is_given_up(2));
+        sender.handle_ack(2, &Ack { fragment_index: 1 });
+        assert!(sender.poll_next_packets().is_empty());
+
+        assert!(sender.remove(2));
+        assert!(sender.is_empty());
+    }
+
+    #[test]
+    fn retransmit_on_timeout() {
+        let clock = ManualClock::new();
+        let buffer = SendBuffer::with_clock(
+            DefaultResendPolicy::default(),
+            SendConfig::default(),
+            clock.clone(),
+        );
+        let mut sender = WindowedSender::with_send_buffer(WindowConfig::default(), buffer);
+        sender.insert(
+            5,
+            NaiveAssembler::disassemble(&[1; 2560]),
+            &route(vec![1, 9]),
+        );
+        sender.poll_next_packets();
+        sender.handle_ack(5, &Ack { fragment_index: 0 });
+        assert_eq!(indexes(&sender.poll_next_packets()), [4]);
+
+        clock.advance(Duration::from_secs(1));
+        // Fragment 4 was sent at the same time as the others
+        assert_eq!(indexes(&sender.poll_next_packets()), [1, 2, 3, 4]);
+        assert_eq!(sender.in_flight(5), Some(4));
+    }
+}